@@ -0,0 +1,175 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use advent_of_code_2023::almanac::{self, Almanac, Day05, SeedFormat, SeedSpec};
+use advent_of_code_2023::boat_race::{Day06, Race};
+use advent_of_code_2023::camel_cards::Day07;
+use advent_of_code_2023::cosmic_expansion::Day11;
+use advent_of_code_2023::cubebag::Day02;
+use advent_of_code_2023::gear_ratios::Day03;
+use advent_of_code_2023::haunted_wasteland::{self, Day08};
+use advent_of_code_2023::hot_springs::{Day12, Spring};
+use advent_of_code_2023::input::InputSource;
+use advent_of_code_2023::oasis::Day09;
+use advent_of_code_2023::pipe_maze::Day10;
+use advent_of_code_2023::scratch_cards::Day04;
+use advent_of_code_2023::solution::Solution;
+use advent_of_code_2023::trebuchet::Day01;
+
+/// Parsing and both parts of a day on its puzzle input, skipped if the input is missing
+fn solution<S: Solution>(c: &mut Criterion) {
+    let Ok(text) = InputSource::Default.read(S::DAY) else {
+        eprintln!("day {:02}: no input, skipped", S::DAY);
+        return;
+    };
+    let input = S::parse_str(&text).unwrap();
+
+    let mut group = c.benchmark_group(format!("day{:02}_{}", S::DAY, S::NAME));
+    group.sample_size(10);
+    group.bench_function("parse", |b| b.iter(|| S::parse(black_box(text.as_bytes()))));
+    group.bench_function("part1", |b| b.iter(|| S::part1(black_box(&input))));
+    group.bench_function("part2", |b| b.iter(|| S::part2(black_box(&input))));
+    group.finish();
+}
+
+fn days(c: &mut Criterion) {
+    solution::<Day01>(c);
+    solution::<Day02>(c);
+    solution::<Day03>(c);
+    solution::<Day04>(c);
+    solution::<Day05>(c);
+    solution::<Day06>(c);
+    solution::<Day07>(c);
+    solution::<Day08>(c);
+    solution::<Day09>(c);
+    solution::<Day10>(c);
+    solution::<Day11>(c);
+    solution::<Day12>(c);
+}
+
+fn ghost_moves(c: &mut Criterion) {
+    let map = haunted_wasteland::parse_str(
+        r#"LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)"#,
+    )
+    .unwrap();
+
+    let mut group = c.benchmark_group("ghost_moves");
+    group.bench_function("bruteforce", |b| {
+        b.iter(|| black_box(&map).moves_ghost_bruteforce())
+    });
+    group.bench_function("lcm", |b| b.iter(|| black_box(&map).moves_ghost()));
+    group.finish();
+}
+
+fn seed_locations(c: &mut Criterion) {
+    let almanac = almanac::parse_str_with::<i64>(
+        r#"seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4"#,
+        SeedFormat::Pairs,
+    )
+    .unwrap();
+    // The same almanac with every seed of the ranges listed on its own
+    let every_seed = Almanac {
+        seeds: SeedSpec::Individual(
+            almanac
+                .seeds
+                .ranges()
+                .unwrap()
+                .into_iter()
+                .flatten()
+                .collect(),
+        ),
+        ..almanac.clone()
+    };
+
+    let mut group = c.benchmark_group("seed_locations");
+    group.bench_function("every_seed", |b| {
+        b.iter(|| black_box(&every_seed).lowest_location())
+    });
+    group.bench_function("ranges", |b| {
+        b.iter(|| black_box(&almanac).lowest_location())
+    });
+    group.finish();
+}
+
+fn winning_strategies(c: &mut Criterion) {
+    let mut group = c.benchmark_group("winning_strategies");
+    group.sample_size(10);
+    for time in [1_000, 100_000, 10_000_000] {
+        let race = Race {
+            time,
+            distance: time * time / 5,
+        };
+        group.bench_with_input(BenchmarkId::from_parameter(time), &race, |b, race| {
+            b.iter(|| race.winning_strategies())
+        });
+    }
+    group.finish();
+}
+
+fn arrangements(c: &mut Criterion) {
+    let mut group = c.benchmark_group("arrangements");
+    group.sample_size(10);
+    for (name, unfold) in [("folded", false), ("unfolded", true)] {
+        let spring = Spring::new("???.###".to_string(), "1,1,3".to_string(), unfold).unwrap();
+        group.bench_with_input(BenchmarkId::new("enumerate", name), &spring, |b, spring| {
+            b.iter(|| spring.clone().get_arrangements())
+        });
+        group.bench_with_input(BenchmarkId::new("count", name), &spring, |b, spring| {
+            b.iter(|| spring.count_arrangements())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    days,
+    ghost_moves,
+    seed_locations,
+    winning_strategies,
+    arrangements
+);
+criterion_main!(benches);
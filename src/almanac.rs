@@ -3,277 +3,805 @@
 
 // 2023-05
 
-use anyhow::{Context, Ok, Result};
+use anyhow::{bail, Context, Result};
+use num::{CheckedAdd, Integer};
+use regex::Regex;
+use std::fmt::{self, Debug, Display};
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+use std::str::FromStr;
+use thiserror::Error;
 
-#[derive(Clone, Debug)]
-pub struct AlmanacInterval {
-    pub start: i64,
-    pub dest: i64,
-    pub length: i64,
+use crate::error::{InputLine, ParseResult};
+use crate::input::LineReader;
+use crate::interval_set::IntervalSet;
+use crate::solution::Solution;
+
+/// Integers an almanac can hold, like `i64`, `u64`, `i128` or `num::BigInt`.
+///
+/// Intervals are checked to end within the type when they are inserted, so converting a
+/// value through them cannot overflow.
+pub trait Value:
+    Integer + CheckedAdd + Clone + Debug + Display + FromStr + Send + Sync + 'static
+{
+}
+
+impl<T> Value for T where
+    T: Integer + CheckedAdd + Clone + Debug + Display + FromStr + Send + Sync + 'static
+{
+}
+
+/// Why an interval cannot be added to a map
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum IntervalError<T: Debug + Display> {
+    #[error("Interval starting at {start} has negative length {length}")]
+    NegativeLength { start: T, length: T },
+    #[error("Interval {start}..+{length} to {dest} ends past the largest value")]
+    Overflow { start: T, dest: T, length: T },
+    #[error("Interval {start}..{end} overlaps interval {other_start}..{other_end}")]
+    Overlap {
+        start: T,
+        end: T,
+        other_start: T,
+        other_end: T,
+    },
+}
+
+/// Maps the half-open source range `start..start + length` to `dest..dest + length`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlmanacInterval<T = i64> {
+    pub start: T,
+    pub dest: T,
+    pub length: T,
+}
+
+impl<T: Value> AlmanacInterval<T> {
+    /// First source after the interval
+    pub fn end(&self) -> T {
+        self.start.clone() + self.length.clone()
+    }
+
+    /// First destination after the interval
+    pub fn dest_end(&self) -> T {
+        self.dest.clone() + self.length.clone()
+    }
+
+    pub fn contains(&self, src: &T) -> bool {
+        self.start <= *src && *src < self.end()
+    }
+
+    /// Where `src` ends up, for any source up to and including `end()`
+    pub fn translate(&self, src: &T) -> T {
+        self.dest.clone() + (src.clone() - self.start.clone())
+    }
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct AlmanacMap {
-    pub ranges: Vec<AlmanacInterval>,
+/// Intervals sorted by their start, with no two sharing a source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlmanacMap<T = i64> {
+    ranges: Vec<AlmanacInterval<T>>,
 }
 
-impl AlmanacMap {
-    pub fn new() -> AlmanacMap {
+impl<T> Default for AlmanacMap<T> {
+    fn default() -> Self {
         AlmanacMap { ranges: Vec::new() }
     }
+}
 
-    pub fn get(&self, src: &i64) -> i64 {
-        for i in &self.ranges {
-            if src >= &i.start && src <= &(i.start + i.length) {
-                return i.dest + (src - i.start);
+impl<T: Value> AlmanacMap<T> {
+    pub fn new() -> AlmanacMap<T> {
+        AlmanacMap::default()
+    }
+
+    pub fn intervals(&self) -> &[AlmanacInterval<T>] {
+        &self.ranges
+    }
+
+    /// Index of the first interval that ends after `src`
+    fn position(&self, src: &T) -> usize {
+        self.ranges.partition_point(|i| i.end() <= *src)
+    }
+
+    /// The interval `src` falls into, if any
+    pub fn find(&self, src: &T) -> Option<&AlmanacInterval<T>> {
+        self.ranges
+            .get(self.position(src))
+            .filter(|i| i.contains(src))
+    }
+
+    pub fn get(&self, src: &T) -> T {
+        match self.find(src) {
+            Some(i) => i.translate(src),
+            None => src.clone(),
+        }
+    }
+
+    pub fn get_ranges(&self, ranges: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = IntervalSet::new();
+        for range in ranges {
+            for (from, to, interval) in self.pieces(range.start.clone(), range.end.clone()) {
+                match interval {
+                    Some(i) => result.insert(i.translate(&from)..i.translate(&to)),
+                    None => result.insert(from..to),
+                }
             }
         }
-        *src
+        result
     }
 
-    pub fn get_ranges(&self, interval: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    /// Splits `start..end` into pieces that each fall into the same interval, or into
+    /// none at all
+    pub(crate) fn pieces(&self, start: T, end: T) -> Vec<(T, T, Option<&AlmanacInterval<T>>)> {
         let mut result = Vec::new();
-        let mut remaining = interval.to_owned();
-        while !remaining.is_empty() {
-            let tmp_interval = remaining.clone();
-            // next_intervals will contain all those which didn't have a mapping this time around
-            let mut next_intervals = Vec::new();
-
-            for (start, length) in tmp_interval {
-                if length == 0 {
-                    continue;
+        let mut index = self.position(&start);
+        let mut position = start;
+        while position < end {
+            let (piece_end, interval) = match self.ranges.get(index) {
+                Some(i) if i.contains(&position) => {
+                    index += 1;
+                    (end.clone().min(i.end()), Some(i))
                 }
+                Some(i) => (end.clone().min(i.start.clone()), None),
+                None => (end.clone(), None),
+            };
+            result.push((position, piece_end.clone(), interval));
+            position = piece_end;
+        }
+        result
+    }
 
-                let my_end = start + length - 1;
-                let prev_result_len = result.len();
-                for i in &self.ranges {
-                    let range_end = i.start + i.length - 1;
-
-                    // tmp is fully contained in range
-                    if start >= i.start && my_end <= range_end {
-                        result.push((i.dest + start - i.start, length));
-                        break;
-                    }
-                    // tmp fully overlaps range
-                    if start <= i.start && my_end >= range_end {
-                        // translate matched part
-                        result.push((i.dest, i.length));
-                        next_intervals.push((start, i.start - start));
-                        next_intervals.push((range_end + 1, my_end - range_end));
-                        break;
-                    }
-                    // tmp overlaps beginning of range
-                    if start < i.start && my_end >= i.start && my_end <= range_end {
-                        result.push((i.dest, my_end - i.start + 1));
-                        next_intervals.push((start, i.start - start));
-                        break;
-                    }
-                    // tmp overlaps end of range
-                    if start > i.start && start <= range_end && my_end >= range_end {
-                        result.push((i.dest + start - i.start, range_end - start + 1));
-                        next_intervals.push((range_end + 1, my_end - range_end));
-                        break;
-                    }
+    /// Builds a map from `(start, end, dest)` pieces that do not overlap, sorted and
+    /// with pieces continuing their neighbour joined
+    fn from_pieces(mut pieces: Vec<(T, T, T)>) -> AlmanacMap<T> {
+        pieces.sort();
+        let mut map = AlmanacMap::<T>::new();
+        for (start, end, dest) in pieces {
+            if dest == start || start >= end {
+                continue;
+            }
+            match map.ranges.last_mut() {
+                Some(last) if last.end() == start && last.dest_end() == dest => {
+                    last.length = last.length.clone() + (end - start)
                 }
+                _ => map.ranges.push(AlmanacInterval {
+                    length: end - start.clone(),
+                    start,
+                    dest,
+                }),
+            }
+        }
+        map
+    }
 
-                let any_results_added = prev_result_len != result.len();
-                if !any_results_added {
-                    result.push((start, length));
+    /// The same mapping in canonical form: sorted, joined and without intervals that map
+    /// to themselves, so that equal mappings compare equal
+    pub fn normalized(&self) -> AlmanacMap<T> {
+        AlmanacMap::from_pieces(
+            self.ranges
+                .iter()
+                .map(|i| (i.start.clone(), i.end(), i.dest.clone()))
+                .collect(),
+        )
+    }
+
+    /// A single map that gives `then.get(&self.get(&src))` for every `src`
+    pub fn compose(&self, then: &AlmanacMap<T>) -> AlmanacMap<T> {
+        let mut pieces = Vec::new();
+        for i in &self.ranges {
+            for (start, end, interval) in then.pieces(i.dest.clone(), i.dest_end()) {
+                let dest = match interval {
+                    Some(j) => j.translate(&start),
+                    None => start.clone(),
+                };
+                let from = i.start.clone() + (start - i.dest.clone());
+                let to = i.start.clone() + (end - i.dest.clone());
+                pieces.push((from, to, dest));
+            }
+        }
+        // Where this map keeps values as they are only `then` applies
+        for j in &then.ranges {
+            for (start, end, interval) in self.pieces(j.start.clone(), j.end()) {
+                if interval.is_none() {
+                    let dest = j.translate(&start);
+                    pieces.push((start, end, dest));
+                }
+            }
+        }
+        AlmanacMap::from_pieces(pieces)
+    }
+
+    /// Every source that `get` maps into `ranges`
+    pub fn preimage(&self, ranges: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = IntervalSet::new();
+        for range in ranges {
+            for i in &self.ranges {
+                let from = range.start.clone().max(i.dest.clone());
+                let to = range.end.clone().min(i.dest_end());
+                if from < to {
+                    result.insert(
+                        i.start.clone() + (from - i.dest.clone())
+                            ..i.start.clone() + (to - i.dest.clone()),
+                    );
+                }
+            }
+            // Values no interval applies to map to themselves
+            for (from, to, interval) in self.pieces(range.start.clone(), range.end.clone()) {
+                if interval.is_none() {
+                    result.insert(from..to);
                 }
             }
-            remaining = next_intervals;
         }
         result
     }
 
-    pub fn insert(&mut self, start: i64, length: i64, dest: i64) {
-        self.ranges.push(AlmanacInterval {
+    /// The map undoing this one, if every value is reached from exactly one source
+    pub fn invert(&self) -> Result<AlmanacMap<T>> {
+        let sources: IntervalSet<T> = self
+            .ranges
+            .iter()
+            .map(|i| i.start.clone()..i.end())
+            .collect();
+        let destinations: IntervalSet<T> = self
+            .ranges
+            .iter()
+            .map(|i| i.dest.clone()..i.dest_end())
+            .collect();
+        if sources != destinations {
+            bail!("Map does not convert its sources onto themselves and cannot be inverted");
+        }
+        let mut inverse = AlmanacMap::new();
+        for i in &self.ranges {
+            inverse
+                .insert(i.dest.clone(), i.length.clone(), i.start.clone())
+                .context("Map cannot be inverted")?;
+        }
+        Ok(inverse)
+    }
+
+    /// Adds an interval, which must not share sources with one already in the map and
+    /// must end within `T` on both sides. Empty intervals map nothing and are left out.
+    pub fn insert(&mut self, start: T, length: T, dest: T) -> Result<(), IntervalError<T>> {
+        if length < T::zero() {
+            return Err(IntervalError::NegativeLength { start, length });
+        }
+        if length.is_zero() {
+            return Ok(());
+        }
+        if start.checked_add(&length).is_none() || dest.checked_add(&length).is_none() {
+            return Err(IntervalError::Overflow {
+                start,
+                dest,
+                length,
+            });
+        }
+        let interval = AlmanacInterval {
             start,
             dest,
             length,
-        });
+        };
+        let index = self.position(&interval.start);
+        if let Some(other) = self.ranges.get(index).filter(|i| i.start < interval.end()) {
+            return Err(IntervalError::Overlap {
+                start: interval.start.clone(),
+                end: interval.end(),
+                other_start: other.start.clone(),
+                other_end: other.end(),
+            });
+        }
+        self.ranges.insert(index, interval);
+        Ok(())
     }
 }
 
-pub fn seeds_as_pairs(seeds: &[i64]) -> Vec<(i64, i64)> {
-    let ranges: Vec<_> = seeds.iter().skip(1).step_by(2).copied().collect();
-    let seeds: Vec<_> = seeds.iter().step_by(2).copied().collect();
-
-    let mut result = Vec::new();
-    for (seed, range) in seeds.iter().zip(ranges.iter()) {
-        result.push((*seed, *range));
-    }
-    result
-}
-
-#[allow(clippy::too_many_arguments)]
-pub fn seed_to_location(
-    seeds: &Vec<i64>,
-    seed_to_soil: &AlmanacMap,
-    soil_to_fertilizer: &AlmanacMap,
-    fertilizer_to_water: &AlmanacMap,
-    water_to_light: &AlmanacMap,
-    light_to_temperature: &AlmanacMap,
-    temperature_to_humidity: &AlmanacMap,
-    humidity_to_location: &AlmanacMap,
-) -> Vec<i64> {
-    let mut result: Vec<i64> = vec![];
-
-    for seed in seeds {
-        let soil = seed_to_soil.get(seed);
-        let fertilizer = soil_to_fertilizer.get(&soil);
-        let water = fertilizer_to_water.get(&fertilizer);
-        let light = water_to_light.get(&water);
-        let temperature = light_to_temperature.get(&light);
-        let humidity = temperature_to_humidity.get(&temperature);
-        let location = humidity_to_location.get(&humidity);
-
-        result.push(location);
-    }
-    result
-}
-
-#[allow(clippy::too_many_arguments)]
-pub fn seed_ranges_to_location(
-    seeds: &Vec<(i64, i64)>,
-    seed_to_soil: &AlmanacMap,
-    soil_to_fertilizer: &AlmanacMap,
-    fertilizer_to_water: &AlmanacMap,
-    water_to_light: &AlmanacMap,
-    light_to_temperature: &AlmanacMap,
-    temperature_to_humidity: &AlmanacMap,
-    humidity_to_location: &AlmanacMap,
-) -> Vec<(i64, i64)> {
-    let mut result: Vec<(i64, i64)> = vec![];
-
-    for seed in seeds {
-        let soil = seed_to_soil.get_ranges(&[*seed]);
-        let fertilizer = soil_to_fertilizer.get_ranges(&soil);
-        let water = fertilizer_to_water.get_ranges(&fertilizer);
-        let light = water_to_light.get_ranges(&water);
-        let temperature = light_to_temperature.get_ranges(&light);
-        let humidity = temperature_to_humidity.get_ranges(&temperature);
-        let location = humidity_to_location.get_ranges(&humidity);
-
-        for loc in location {
-            result.push(loc);
-        }
-    }
-    result
-}
-
-#[allow(clippy::type_complexity)]
-pub fn parse(
-    lines: &Vec<String>,
-) -> Result<(
-    Vec<i64>,
-    AlmanacMap,
-    AlmanacMap,
-    AlmanacMap,
-    AlmanacMap,
-    AlmanacMap,
-    AlmanacMap,
-    AlmanacMap,
-)> {
-    let mut seeds = vec![];
-    let mut seed_to_soil = AlmanacMap::new();
-    let mut soil_to_fertilizer = AlmanacMap::new();
-    let mut fertilizer_to_water = AlmanacMap::new();
-    let mut water_to_light = AlmanacMap::new();
-    let mut light_to_temperature = AlmanacMap::new();
-    let mut temperature_to_humidity = AlmanacMap::new();
-    let mut humidity_to_location = AlmanacMap::new();
-
-    let mut maps = 18;
-
-    for line in lines {
-        if line.starts_with("seeds: ") {
-            seeds = line
-                .split(':')
-                .last()
-                .context("Nothing behind colon")?
-                .split(' ')
-                .filter(|s| !s.is_empty())
-                .map(|s| s.parse::<i64>().expect("Couldn't parse seed string"))
-                .collect();
-            continue;
+/// Writes a `destination source length` line per interval, as given in the puzzle
+impl<T: Display> Display for AlmanacMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in &self.ranges {
+            writeln!(f, "{} {} {}", i.dest, i.start, i.length)?;
         }
-        match line.as_str() {
-            "seed-to-soil map:" => {
-                maps = 0;
-                continue;
-            }
-            "soil-to-fertilizer map:" => {
-                maps = 1;
-                continue;
-            }
-            "fertilizer-to-water map:" => {
-                maps = 2;
-                continue;
+        Ok(())
+    }
+}
+
+/// How the numbers of the `seeds:` line are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedFormat {
+    /// Every number is a seed of its own
+    Individual,
+    /// `start length` pairs
+    Pairs,
+    /// `first last` pairs, both seeds included
+    Inclusive,
+}
+
+/// The seeds of an almanac, read in one of the `SeedFormat`s
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeedSpec<T = i64> {
+    Individual(Vec<T>),
+    Pairs(Vec<(T, T)>),
+    Inclusive(Vec<(T, T)>),
+}
+
+impl<T> Default for SeedSpec<T> {
+    fn default() -> Self {
+        SeedSpec::Individual(Vec::new())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Seeds read as {format:?} need an even number of values, found {count}")]
+pub struct SeedCountError {
+    pub format: SeedFormat,
+    pub count: usize,
+}
+
+impl<T: Value> SeedSpec<T> {
+    /// Reads `values` in `format`, which for pairs must have an even count
+    pub fn new(format: SeedFormat, values: Vec<T>) -> Result<SeedSpec<T>, SeedCountError> {
+        if format == SeedFormat::Individual {
+            return Ok(SeedSpec::Individual(values));
+        }
+        if !values.len().is_multiple_of(2) {
+            return Err(SeedCountError {
+                format,
+                count: values.len(),
+            });
+        }
+        let pairs = values
+            .chunks_exact(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+        Ok(match format {
+            SeedFormat::Pairs => SeedSpec::Pairs(pairs),
+            _ => SeedSpec::Inclusive(pairs),
+        })
+    }
+
+    pub fn format(&self) -> SeedFormat {
+        match self {
+            SeedSpec::Individual(_) => SeedFormat::Individual,
+            SeedSpec::Pairs(_) => SeedFormat::Pairs,
+            SeedSpec::Inclusive(_) => SeedFormat::Inclusive,
+        }
+    }
+
+    /// The numbers as they are given on the `seeds:` line
+    pub fn values(&self) -> Vec<T> {
+        match self {
+            SeedSpec::Individual(seeds) => seeds.clone(),
+            SeedSpec::Pairs(pairs) | SeedSpec::Inclusive(pairs) => pairs
+                .iter()
+                .flat_map(|(a, b)| [a.clone(), b.clone()])
+                .collect(),
+        }
+    }
+
+    /// Every seed, erroring if a range ends past the largest value of `T`
+    pub fn ranges(&self) -> Result<IntervalSet<T>> {
+        let after = |last: &T| {
+            last.checked_add(&T::one())
+                .with_context(|| format!("Seed {last} is the largest value and has no end"))
+        };
+        let mut result = IntervalSet::new();
+        match self {
+            SeedSpec::Individual(seeds) => {
+                for seed in seeds {
+                    result.insert(seed.clone()..after(seed)?);
+                }
             }
-            "water-to-light map:" => {
-                maps = 3;
-                continue;
+            SeedSpec::Pairs(pairs) => {
+                for (start, length) in pairs {
+                    let end = start.checked_add(length).with_context(|| {
+                        format!("Seed range starting at {start} of length {length} ends past the largest value")
+                    })?;
+                    result.insert(start.clone()..end);
+                }
             }
-            "light-to-temperature map:" => {
-                maps = 4;
-                continue;
+            SeedSpec::Inclusive(pairs) => {
+                for (first, last) in pairs {
+                    result.insert(first.clone()..after(last)?);
+                }
             }
-            "temperature-to-humidity map:" => {
-                maps = 5;
-                continue;
+        }
+        Ok(result)
+    }
+}
+
+/// The lowest location any seed reaches, and the lowest seed reaching it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedLocation<T = i64> {
+    pub seed: T,
+    pub location: T,
+}
+
+/// Category names the puzzle starts from and asks for
+pub const SEED: &str = "seed";
+pub const LOCATION: &str = "location";
+
+/// The map given under an `X-to-Y map:` header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlmanacStage<T = i64> {
+    pub source: String,
+    pub destination: String,
+    pub map: AlmanacMap<T>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Almanac<T = i64> {
+    pub seeds: SeedSpec<T>,
+    /// In the order of their headers. No two convert between the same categories and
+    /// following them never leads back to a category already passed.
+    pub stages: Vec<AlmanacStage<T>>,
+}
+
+impl<T> Default for Almanac<T> {
+    fn default() -> Self {
+        Almanac {
+            seeds: SeedSpec::default(),
+            stages: Vec::new(),
+        }
+    }
+}
+
+impl<T: Display> Display for AlmanacStage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}-to-{} map:", self.source, self.destination)?;
+        write!(f, "{}", self.map)
+    }
+}
+
+/// Writes the almanac in the puzzle's format, which `parse_with` reads back given the
+/// format of its seeds
+impl<T: Value> Display for Almanac<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seeds:")?;
+        for value in self.seeds.values() {
+            write!(f, " {value}")?;
+        }
+        writeln!(f)?;
+        for stage in &self.stages {
+            write!(f, "\n{stage}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What one stage did to a traced value `V`, or to a piece of a traced range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep<'a, T = i64, V = T> {
+    pub stage: &'a AlmanacStage<T>,
+    pub input: V,
+    pub output: V,
+    /// The interval converting the input, `None` if it was kept as it is
+    pub interval: Option<&'a AlmanacInterval<T>>,
+}
+
+impl<T: Value> Almanac<T> {
+    /// Every category in the order it is first named by a header
+    pub fn categories(&self) -> Vec<&str> {
+        let mut result: Vec<&str> = Vec::new();
+        for stage in &self.stages {
+            for category in [&stage.source, &stage.destination] {
+                if !result.contains(&category.as_str()) {
+                    result.push(category);
+                }
             }
-            "humidity-to-location map:" => {
-                maps = 6;
-                continue;
+        }
+        result
+    }
+
+    /// The stages leading from `from` to `to`, which must be connected in exactly one way
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<&AlmanacStage<T>>> {
+        let categories = self.categories();
+        for category in [from, to] {
+            if !categories.contains(&category) {
+                bail!("Unknown category {category}");
             }
-            "" => {
-                continue;
+        }
+        let mut paths = self.paths(from, to);
+        match paths.len() {
+            0 => bail!("No maps lead from {from} to {to}"),
+            1 => Ok(paths.remove(0)),
+            n => bail!("{n} different chains of maps lead from {from} to {to}"),
+        }
+    }
+
+    /// All chains of stages from `from` to `to`, terminates as the stages form no cycle
+    fn paths(&self, from: &str, to: &str) -> Vec<Vec<&AlmanacStage<T>>> {
+        if from == to {
+            return vec![Vec::new()];
+        }
+        let mut result = Vec::new();
+        for stage in self.stages.iter().filter(|s| s.source == from) {
+            for mut path in self.paths(&stage.destination, to) {
+                path.insert(0, stage);
+                result.push(path);
             }
-            &_ => { // Do nothing, normal parsing
+        }
+        result
+    }
+
+    /// Converts the value of category `from` to category `to`
+    pub fn convert(&self, from: &str, to: &str, value: T) -> Result<T> {
+        Ok(self
+            .path(from, to)?
+            .iter()
+            .fold(value, |value, stage| stage.map.get(&value)))
+    }
+
+    /// Converts ranges of category `from` to category `to`
+    pub fn convert_ranges(
+        &self,
+        from: &str,
+        to: &str,
+        ranges: &IntervalSet<T>,
+    ) -> Result<IntervalSet<T>> {
+        Ok(self
+            .path(from, to)?
+            .iter()
+            .fold(ranges.clone(), |ranges, stage| {
+                stage.map.get_ranges(&ranges)
+            }))
+    }
+
+    /// Every value of category `from` that converts into `ranges` of category `to`
+    pub fn preimage(
+        &self,
+        from: &str,
+        to: &str,
+        ranges: &IntervalSet<T>,
+    ) -> Result<IntervalSet<T>> {
+        Ok(self
+            .path(from, to)?
+            .iter()
+            .rev()
+            .fold(ranges.clone(), |ranges, stage| stage.map.preimage(&ranges)))
+    }
+
+    /// Every conversion `value` goes through on its way from `from` to `to`
+    pub fn trace(&self, from: &str, to: &str, value: T) -> Result<Vec<TraceStep<'_, T>>> {
+        let mut result = Vec::new();
+        let mut value = value;
+        for stage in self.path(from, to)? {
+            let output = stage.map.get(&value);
+            result.push(TraceStep {
+                stage,
+                interval: stage.map.find(&value),
+                input: value,
+                output: output.clone(),
+            });
+            value = output;
+        }
+        Ok(result)
+    }
+
+    /// Like `trace`, with a step for every piece the ranges are split into by a stage
+    pub fn trace_ranges(
+        &self,
+        from: &str,
+        to: &str,
+        ranges: &IntervalSet<T>,
+    ) -> Result<Vec<TraceStep<'_, T, Range<T>>>> {
+        let mut result = Vec::new();
+        let mut ranges = ranges.clone();
+        for stage in self.path(from, to)? {
+            for range in &ranges {
+                for (start, end, interval) in
+                    stage.map.pieces(range.start.clone(), range.end.clone())
+                {
+                    let output = match interval {
+                        Some(i) => i.translate(&start)..i.translate(&end),
+                        None => start.clone()..end.clone(),
+                    };
+                    result.push(TraceStep {
+                        stage,
+                        input: start..end,
+                        output,
+                        interval,
+                    });
+                }
             }
+            ranges = stage.map.get_ranges(&ranges);
+        }
+        Ok(result)
+    }
+
+    /// One map doing all conversions from `from` to `to` at once
+    pub fn collapse(&self, from: &str, to: &str) -> Result<AlmanacMap<T>> {
+        Ok(self
+            .path(from, to)?
+            .iter()
+            .fold(AlmanacMap::new(), |map, stage| map.compose(&stage.map)))
+    }
+
+    /// Every location the seeds reach
+    pub fn locations(&self) -> Result<IntervalSet<T>> {
+        let map = self.collapse(SEED, LOCATION)?;
+        Ok(map.get_ranges(&self.seeds.ranges()?))
+    }
+
+    /// Solves the almanac: finds the lowest location of all seeds, whichever way they
+    /// are given, together with the seed leading there
+    pub fn lowest_location(&self) -> Result<SeedLocation<T>> {
+        let map = self.collapse(SEED, LOCATION)?;
+        let seeds = self.seeds.ranges()?;
+        let location = map.get_ranges(&seeds).min().context("No seeds given")?;
+        let reaching = map.preimage(&IntervalSet::from_iter([
+            location.clone()..location.clone() + T::one()
+        ]));
+        let seed = reaching
+            .intersection(&seeds)
+            .min()
+            .context("No seed reaches the lowest location")?;
+        Ok(SeedLocation { seed, location })
+    }
+
+    /// Adds an empty stage for `header`, provided it fits in with those before it
+    fn add_stage(
+        &mut self,
+        source: &str,
+        destination: &str,
+        header: &InputLine,
+    ) -> ParseResult<()> {
+        if self
+            .stages
+            .iter()
+            .any(|s| s.source == source && s.destination == destination)
+        {
+            return Err(header.unexpected(header.text, "map not given before"));
+        }
+        if !self.paths(destination, source).is_empty() {
+            return Err(header.unexpected(header.text, "map that does not close a cycle"));
+        }
+        self.stages.push(AlmanacStage {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            map: AlmanacMap::new(),
+        });
+        Ok(())
+    }
+}
+
+/// Writes a trace as a table with a line per stage
+pub fn write_trace<W: Write, T: Value>(mut out: W, steps: &[TraceStep<T>]) -> io::Result<()> {
+    let mut rows = vec![["stage", "input", "output", "interval"].map(String::from)];
+    for step in steps {
+        rows.push([
+            format!("{}-to-{}", step.stage.source, step.stage.destination),
+            step.input.to_string(),
+            step.output.to_string(),
+            match step.interval {
+                Some(i) => format!("{} {} {}", i.dest, i.start, i.length),
+                None => "identity".to_string(),
+            },
+        ]);
+    }
+    let mut widths = [0; 4];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = cell.len().max(*width);
+        }
+    }
+    for [stage, input, output, interval] in &rows {
+        writeln!(
+            out,
+            "{stage:<0$}  {input:>1$}  {output:>2$}  {interval}",
+            widths[0], widths[1], widths[2]
+        )?;
+    }
+    Ok(())
+}
+
+/// Parses an almanac holding values of type `T`, reading its seeds in `format`
+pub fn parse_with<T: Value, R: BufRead>(input: R, format: SeedFormat) -> ParseResult<Almanac<T>> {
+    let header = Regex::new(r"^(?<source>\w+)-to-(?<destination>\w+) map:$")
+        .expect("Could not compile regex");
+    let mut almanac = Almanac {
+        seeds: SeedSpec::new(format, Vec::new()).expect("No seeds are an even number"),
+        stages: Vec::new(),
+    };
+
+    let mut lines = LineReader::new(Day05::DAY, input);
+    while let Some(line) = lines.next_line()? {
+        if let Some(seed_list) = line.text.strip_prefix("seeds:") {
+            let seed_list = seed_list.trim_start();
+            let values = seed_list
+                .split(' ')
+                .filter(|s| !s.is_empty())
+                .map(|s| line.number::<T>(s))
+                .collect::<ParseResult<Vec<_>>>()?;
+            almanac.seeds = SeedSpec::new(format, values)
+                .map_err(|_| line.unexpected(seed_list, "an even number of seed values"))?;
+            continue;
         }
-        let numbers = line
+        if line.text.is_empty() {
+            continue;
+        }
+        if let Some(captures) = header.captures(line.text) {
+            almanac.add_stage(&captures["source"], &captures["destination"], &line)?;
+            continue;
+        }
+        if line.text.ends_with(" map:") {
+            return Err(line.unexpected(line.text, "map header like \"seed-to-soil map:\""));
+        }
+        let mut numbers = line
+            .text
             .split(' ')
             .filter(|s| !s.is_empty())
-            .map(|s| s.parse::<i64>().expect("Couldn't parse map string"))
-            .collect::<Vec<_>>();
-        if maps == 0 {
-            seed_to_soil.insert(numbers[1], numbers[2], numbers[0]);
-        } else if maps == 1 {
-            soil_to_fertilizer.insert(numbers[1], numbers[2], numbers[0]);
-        } else if maps == 2 {
-            fertilizer_to_water.insert(numbers[1], numbers[2], numbers[0]);
-        } else if maps == 3 {
-            water_to_light.insert(numbers[1], numbers[2], numbers[0]);
-        } else if maps == 4 {
-            light_to_temperature.insert(numbers[1], numbers[2], numbers[0]);
-        } else if maps == 5 {
-            temperature_to_humidity.insert(numbers[1], numbers[2], numbers[0]);
-        } else if maps == 6 {
-            humidity_to_location.insert(numbers[1], numbers[2], numbers[0]);
-        }
-    }
-    let result = (
-        seeds,
-        seed_to_soil,
-        soil_to_fertilizer,
-        fertilizer_to_water,
-        water_to_light,
-        light_to_temperature,
-        temperature_to_humidity,
-        humidity_to_location,
-    );
-    Ok(result)
+            .map(|s| line.number::<T>(s))
+            .collect::<ParseResult<Vec<_>>>()?;
+        if numbers.len() != 3 {
+            return Err(line.unexpected(line.text, "destination source length"));
+        }
+        let Some(stage) = almanac.stages.last_mut() else {
+            return Err(line.unexpected(line.text, "map header"));
+        };
+        let (length, start, dest) = (numbers.remove(2), numbers.remove(1), numbers.remove(0));
+        if let Err(error) = stage.map.insert(start, length, dest) {
+            let expected = match error {
+                IntervalError::NegativeLength { .. } => "non-negative length",
+                IntervalError::Overflow { .. } => "interval ending within the number type",
+                IntervalError::Overlap { .. } => "interval not overlapping earlier ones",
+            };
+            return Err(line.unexpected(line.text, expected));
+        }
+    }
+    Ok(almanac)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub fn parse_str_with<T: Value>(text: &str, format: SeedFormat) -> ParseResult<Almanac<T>> {
+    parse_with(text.as_bytes(), format)
+}
+
+/// Parses an almanac holding values of type `T` with every seed given on its own
+pub fn parse_as<T: Value, R: BufRead>(input: R) -> ParseResult<Almanac<T>> {
+    parse_with(input, SeedFormat::Individual)
+}
+
+pub fn parse_str_as<T: Value>(text: &str) -> ParseResult<Almanac<T>> {
+    parse_as(text.as_bytes())
+}
+
+pub fn parse<R: BufRead>(input: R) -> ParseResult<Almanac> {
+    parse_as(input)
+}
+
+pub fn parse_str(text: &str) -> ParseResult<Almanac> {
+    parse_str_as(text)
+}
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    const NAME: &'static str = "almanac";
+
+    type Input = Almanac;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i64> {
+        Ok(input.lowest_location()?.location)
+    }
+
+    fn part2(input: &Self::Input) -> Result<i64> {
+        let ranges = Almanac {
+            seeds: SeedSpec::new(SeedFormat::Pairs, input.seeds.values())?,
+            ..input.clone()
+        };
+        Ok(ranges.lowest_location()?.location)
+    }
+}
 
-    fn example() -> Vec<String> {
-        // destination source length
-        r#"seeds: 79 14 55 13
+/// The example almanac from the puzzle, also plotted in the tests of `almanac_plot`
+#[cfg(test)]
+pub(crate) fn example() -> &'static str {
+    // destination source length
+    r#"seeds: 79 14 55 13
 
 seed-to-soil map:
 50 98 2
@@ -306,103 +834,596 @@ temperature-to-humidity map:
 humidity-to-location map:
 60 56 37
 56 93 4"#
-            .split('\n')
-            .map(|s| s.to_string())
-            .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ParseError;
+    use num::BigInt;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    fn set(pairs: &[(i64, i64)]) -> IntervalSet {
+        IntervalSet::from_pairs(pairs)
     }
 
     #[test]
     fn location_web() {
-        let (
-            seeds,
-            seed_to_soil,
-            soil_to_fertilizer,
-            fertilizer_to_water,
-            water_to_light,
-            light_to_temperature,
-            temperature_to_humidity,
-            humidity_to_location,
-        ) = parse(&example()).unwrap();
+        let almanac = parse_str(example()).unwrap();
+        assert_eq!(
+            vec![
+                "seed",
+                "soil",
+                "fertilizer",
+                "water",
+                "light",
+                "temperature",
+                "humidity",
+                "location"
+            ],
+            almanac.categories()
+        );
+        let seed_to_soil = &almanac.stages[0].map;
         assert_eq!(10, seed_to_soil.get(&10));
         assert_eq!(0, seed_to_soil.get(&0));
-        let locations = seed_to_location(
-            &seeds,
-            &seed_to_soil,
-            &soil_to_fertilizer,
-            &fertilizer_to_water,
-            &water_to_light,
-            &light_to_temperature,
-            &temperature_to_humidity,
-            &humidity_to_location,
+        assert_eq!(
+            set(&[(35, 1), (43, 1), (82, 1), (86, 1)]),
+            almanac.locations().unwrap()
+        );
+        assert_eq!(
+            SeedLocation {
+                seed: 13,
+                location: 35
+            },
+            almanac.lowest_location().unwrap()
         );
-        assert_eq!(vec![82, 43, 86, 35], locations);
+        assert_eq!(74, almanac.convert("seed", "light", 79).unwrap());
+        assert_eq!(78, almanac.convert("light", "temperature", 74).unwrap());
+        assert_eq!(79, almanac.convert("seed", "seed", 79).unwrap());
+    }
+
+    #[test]
+    fn chains() {
+        // Stages out of order, with the water step left out and an extra one added
+        let almanac = parse_str(
+            r#"seeds: 1 7
+
+soil-to-location map:
+10 0 5
+
+seed-to-soil map:
+2 1 1
+
+location-to-parcel map:
+
+seed-to-dust map:
+0 0 100"#,
+        )
+        .unwrap();
+        assert_eq!(set(&[(7, 1), (12, 1)]), almanac.locations().unwrap());
+        assert_eq!(12, almanac.convert("seed", "parcel", 1).unwrap());
+        assert_eq!(1, almanac.convert("seed", "dust", 1).unwrap());
+        assert!(almanac.convert("soil", "seed", 1).is_err());
+        assert!(almanac.convert("seed", "water", 1).is_err());
+
+        // A second way from seed to location
+        let mut almanac = almanac;
+        almanac.stages.push(AlmanacStage {
+            source: "dust".to_string(),
+            destination: "location".to_string(),
+            map: AlmanacMap::new(),
+        });
+        assert!(almanac.locations().is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let lines = example().replace("seeds: 79 14", "seeds: 79 1x4");
+        assert_eq!(
+            ParseError::InvalidNumber {
+                day: 5,
+                line: 1,
+                column: 11,
+                found: "1x4".to_string()
+            },
+            parse_str(&lines).unwrap_err()
+        );
+
+        let lines = example().replace("52 50 48", "52 50");
+        assert!(matches!(
+            parse_str(&lines).unwrap_err(),
+            ParseError::Unexpected { line: 5, .. }
+        ));
+
+        let lines = example().replace("seed-to-soil map:", "seed to soil map:");
+        assert!(matches!(
+            parse_str(&lines).unwrap_err(),
+            ParseError::Unexpected { line: 3, .. }
+        ));
+
+        let lines = example().replace("39 0 15", "39 0 16");
+        assert!(matches!(
+            parse_str(&lines).unwrap_err(),
+            ParseError::Unexpected {
+                line: 10,
+                expected: "interval not overlapping earlier ones",
+                ..
+            }
+        ));
+
+        let lines = example().replace("light-to-temperature", "light-to-seed");
+        assert_eq!(
+            ParseError::Unexpected {
+                day: 5,
+                line: 22,
+                column: 1,
+                expected: "map that does not close a cycle",
+                found: "light-to-seed map:".to_string()
+            },
+            parse_str(&lines).unwrap_err()
+        );
+
+        let lines = example().replace("water-to-light", "seed-to-soil");
+        assert!(matches!(
+            parse_str(&lines).unwrap_err(),
+            ParseError::Unexpected {
+                line: 18,
+                expected: "map not given before",
+                ..
+            }
+        ));
     }
 
     #[test]
     fn map_get_ranges() {
         let mut map = AlmanacMap::new();
-        map.insert(5, 5, 50);
+        map.insert(5, 5, 50).unwrap();
 
-        assert_eq!(vec![(1, 2)], map.get_ranges(&vec![(1, 2)]));
-        assert_eq!(vec![(50, 1), (4, 1)], map.get_ranges(&vec![(4, 2)]));
-        assert_eq!(vec![(54, 1), (10, 2)], map.get_ranges(&vec![(9, 3)]));
-        assert_eq!(vec![(52, 2)], map.get_ranges(&vec![(7, 2)]));
+        assert_eq!(set(&[(1, 2)]), map.get_ranges(&set(&[(1, 2)])));
+        assert_eq!(set(&[(4, 1), (50, 1)]), map.get_ranges(&set(&[(4, 2)])));
+        assert_eq!(set(&[(54, 1), (10, 2)]), map.get_ranges(&set(&[(9, 3)])));
+        assert_eq!(set(&[(52, 2)]), map.get_ranges(&set(&[(7, 2)])));
         assert_eq!(
-            vec![(50, 5), (4, 1), (10, 1)],
-            map.get_ranges(&vec![(4, 7)])
+            set(&[(4, 1), (50, 5), (10, 1)]),
+            map.get_ranges(&set(&[(4, 7)]))
         );
 
         let mut map = AlmanacMap::new();
-        map.insert(5, 5, 50);
-        map.insert(100, 5, 150);
-        assert_eq!(vec![(70, 1)], map.get_ranges(&vec![(70, 1)]));
-        assert_eq!(vec![(150, 1)], map.get_ranges(&vec![(100, 1)]));
+        map.insert(5, 5, 50).unwrap();
+        map.insert(100, 5, 150).unwrap();
+        assert_eq!(set(&[(70, 1)]), map.get_ranges(&set(&[(70, 1)])));
+        assert_eq!(set(&[(150, 1)]), map.get_ranges(&set(&[(100, 1)])));
         assert_eq!(
-            vec![(4, 1), (150, 1)],
-            map.get_ranges(&vec![(4, 1), (100, 1)])
+            set(&[(4, 1), (150, 1)]),
+            map.get_ranges(&set(&[(4, 1), (100, 1)]))
         );
         assert_eq!(
-            vec![(4, 1), (54, 1), (150, 1), (10, 1)],
-            map.get_ranges(&vec![(4, 1), (9, 2), (100, 1)])
+            set(&[(4, 1), (54, 1), (10, 1), (150, 1)]),
+            map.get_ranges(&set(&[(4, 1), (9, 2), (100, 1)]))
         );
 
         let mut map = AlmanacMap::new();
-        map.insert(1, 1, 10);
-        map.insert(2, 1, 100);
-        assert_eq!(vec![(100, 1)], map.get_ranges(&vec![(2, 1)]));
-        assert_eq!(vec![(10, 1)], map.get_ranges(&vec![(1, 1)]));
-        assert_eq!(vec![(10, 1), (100, 1)], map.get_ranges(&vec![(1, 2)]));
+        map.insert(1, 1, 10).unwrap();
+        map.insert(2, 1, 100).unwrap();
+        assert_eq!(set(&[(100, 1)]), map.get_ranges(&set(&[(2, 1)])));
+        assert_eq!(set(&[(10, 1)]), map.get_ranges(&set(&[(1, 1)])));
+        assert_eq!(set(&[(10, 1), (100, 1)]), map.get_ranges(&set(&[(1, 2)])));
+
+        // The last source of an interval is mapped, the one after it is not
+        assert_eq!(100, map.get(&2));
+        assert_eq!(3, map.get(&3));
+    }
+
+    #[test]
+    fn map_insert() {
+        let mut map = AlmanacMap::new();
+        map.insert(10, 5, 0).unwrap();
+        map.insert(0, 5, 100).unwrap();
+        map.insert(5, 5, 200).unwrap();
+        map.insert(7, 0, 300).unwrap();
+        assert_eq!(
+            vec![0, 5, 10],
+            map.intervals().iter().map(|i| i.start).collect::<Vec<_>>()
+        );
+        assert!(map.insert(14, 2, 50).is_err());
+        assert!(map.insert(-1, 2, 50).is_err());
+        assert!(map.insert(8, 1, 50).is_err());
+        assert!(map.insert(20, -1, 50).is_err());
+        assert_eq!(Some(&map.intervals()[1]), map.find(&9));
+        assert_eq!(None, map.find(&15));
+    }
+
+    #[test]
+    fn compose() {
+        let mut first = AlmanacMap::new();
+        first.insert(0, 10, 20).unwrap();
+        let mut then = AlmanacMap::new();
+        then.insert(25, 10, 100).unwrap();
+        then.insert(5, 2, 0).unwrap();
+
+        let mut expected = AlmanacMap::new();
+        expected.insert(0, 5, 20).unwrap();
+        expected.insert(5, 5, 100).unwrap();
+        expected.insert(25, 10, 100).unwrap();
+        assert_eq!(expected, first.compose(&then));
+
+        // Swapping twice is no mapping at all
+        let mut swap = AlmanacMap::new();
+        swap.insert(20, 10, 0).unwrap();
+        swap.insert(0, 10, 20).unwrap();
+        assert_eq!(AlmanacMap::new(), swap.compose(&swap));
+        assert_eq!(first.normalized(), first.compose(&AlmanacMap::new()));
+        assert_eq!(then.normalized(), AlmanacMap::new().compose(&then));
+    }
+
+    #[test]
+    fn invert() {
+        let almanac = parse_str(example()).unwrap();
+        for stage in &almanac.stages {
+            let inverse = stage.map.invert().unwrap();
+            assert_eq!(AlmanacMap::new(), stage.map.compose(&inverse));
+            assert_eq!(AlmanacMap::new(), inverse.compose(&stage.map));
+        }
+
+        let mut map = AlmanacMap::new();
+        map.insert(0, 10, 20).unwrap();
+        assert!(map.invert().is_err());
+        map.insert(20, 10, 5).unwrap();
+        assert!(map.invert().is_err());
+    }
+
+    #[test]
+    fn preimage_web() {
+        let almanac = parse_str(example()).unwrap();
+        let seeds = almanac.preimage(SEED, LOCATION, &set(&[(46, 1)])).unwrap();
+        assert_eq!(set(&[(82, 1)]), seeds);
+
+        // Which seeds of the ranges reach a location below 47
+        let below = almanac.preimage(SEED, LOCATION, &set(&[(0, 47)])).unwrap();
+        let ranges = set(&[(79, 14), (55, 13)]);
+        assert_eq!(set(&[(82, 1)]), below.intersection(&ranges));
+    }
+
+    #[test]
+    fn trace_web() {
+        let almanac = parse_str(example()).unwrap();
+        let steps = almanac.trace(SEED, LOCATION, 79).unwrap();
+        assert_eq!(
+            vec![79, 81, 81, 81, 74, 78, 78],
+            steps.iter().map(|s| s.input).collect::<Vec<_>>()
+        );
+        assert_eq!(82, steps[6].output);
+        assert_eq!(
+            Some(&almanac.stages[0].map.intervals()[0]),
+            steps[0].interval
+        );
+        assert_eq!(None, steps[1].interval);
+
+        let mut table = Vec::new();
+        write_trace(&mut table, &steps[..3]).unwrap();
+        assert_eq!(
+            "stage                input  output  interval\n\
+             seed-to-soil            79      81  52 50 48\n\
+             soil-to-fertilizer      81      81  identity\n\
+             fertilizer-to-water     81      81  identity\n",
+            String::from_utf8(table).unwrap()
+        );
+
+        let steps = almanac
+            .trace_ranges(SEED, "soil", &set(&[(96, 4)]))
+            .unwrap();
+        assert_eq!(
+            vec![(96..98, 98..100), (98..100, 50..52)],
+            steps
+                .into_iter()
+                .map(|s| (s.input, s.output))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn collapse_web() {
+        let almanac = parse_str(example()).unwrap();
+        let map = almanac.collapse(SEED, LOCATION).unwrap();
+        assert_eq!(vec![82, 43, 86, 35], [79, 14, 55, 13].map(|s| map.get(&s)));
+        for seed in 0..120 {
+            assert_eq!(
+                almanac.convert(SEED, LOCATION, seed).unwrap(),
+                map.get(&seed)
+            );
+        }
     }
 
     #[test]
     fn location_range_web() {
-        let (
-            seeds,
-            seed_to_soil,
-            soil_to_fertilizer,
-            fertilizer_to_water,
-            water_to_light,
-            light_to_temperature,
-            temperature_to_humidity,
-            humidity_to_location,
-        ) = parse(&example()).unwrap();
+        let almanac = parse_str_with(example(), SeedFormat::Pairs).unwrap();
 
         // First the winning seed
-        assert_eq!(vec![(84, 1)], seed_to_soil.get_ranges(&vec![(82, 1)]));
-
-        let seeds = seeds_as_pairs(&seeds);
-        let locations = seed_ranges_to_location(
-            &seeds,
-            &seed_to_soil,
-            &soil_to_fertilizer,
-            &fertilizer_to_water,
-            &water_to_light,
-            &light_to_temperature,
-            &temperature_to_humidity,
-            &humidity_to_location,
+        assert_eq!(
+            set(&[(84, 1)]),
+            almanac.stages[0].map.get_ranges(&set(&[(82, 1)]))
+        );
+
+        let locations = almanac.locations().unwrap();
+        assert_eq!(Some(46), locations.min());
+        // Every seed in the ranges ends up somewhere different
+        assert_eq!(27, locations.total_length());
+        assert_eq!(
+            SeedLocation {
+                seed: 82,
+                location: 46
+            },
+            almanac.lowest_location().unwrap()
+        );
+    }
+
+    #[test]
+    fn seed_formats() {
+        let inclusive = example().replace("seeds: 79 14 55 13", "seeds: 79 92 55 67");
+        let almanac = parse_str_with(&inclusive, SeedFormat::Inclusive).unwrap();
+        assert_eq!(SeedSpec::Inclusive(vec![(79, 92), (55, 67)]), almanac.seeds);
+        assert_eq!(set(&[(79, 14), (55, 13)]), almanac.seeds.ranges().unwrap());
+        assert_eq!(46, almanac.lowest_location().unwrap().location);
+
+        let pairs = SeedSpec::new(SeedFormat::Pairs, vec![79, 14, 55, 13]).unwrap();
+        assert_eq!(SeedFormat::Pairs, pairs.format());
+        assert_eq!(vec![79, 14, 55, 13], pairs.values());
+        assert_eq!(
+            Err(SeedCountError {
+                format: SeedFormat::Inclusive,
+                count: 3
+            }),
+            SeedSpec::new(SeedFormat::Inclusive, vec![79, 14, 55])
+        );
+
+        // An odd count is fine for single seeds but not for pairs
+        let odd = example().replace("seeds: 79 14 55 13", "seeds: 79 14 55");
+        let almanac = parse_str(&odd).unwrap();
+        assert_eq!(SeedSpec::Individual(vec![79, 14, 55]), almanac.seeds);
+        assert_eq!(43, Day05::part1(&almanac).unwrap());
+        assert!(Day05::part2(&almanac).is_err());
+        assert_eq!(
+            ParseError::Unexpected {
+                day: 5,
+                line: 1,
+                column: 8,
+                expected: "an even number of seed values",
+                found: "79 14 55".to_string()
+            },
+            parse_str_with::<i64>(&odd, SeedFormat::Pairs).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn write_web() {
+        let almanac = parse_str_with::<i64>(example(), SeedFormat::Pairs).unwrap();
+        let text = almanac.to_string();
+        assert!(text.starts_with("seeds: 79 14 55 13\n\nseed-to-soil map:\n52 50 48\n50 98 2\n\n"));
+        assert!(text.ends_with("\nhumidity-to-location map:\n60 56 37\n56 93 4\n"));
+        assert_eq!(almanac, parse_str_with(&text, SeedFormat::Pairs).unwrap());
+        assert_eq!("1 0 69\n0 69 1\n", almanac.stages[5].map.to_string());
+
+        // Saved as a single map from seed to location
+        let collapsed = Almanac {
+            seeds: almanac.seeds.clone(),
+            stages: vec![AlmanacStage {
+                source: SEED.to_string(),
+                destination: LOCATION.to_string(),
+                map: almanac.collapse(SEED, LOCATION).unwrap(),
+            }],
+        };
+        let reread = parse_str_with(&collapsed.to_string(), SeedFormat::Pairs).unwrap();
+        assert_eq!(
+            almanac.lowest_location().unwrap(),
+            reread.lowest_location().unwrap()
+        );
+
+        let empty = Almanac::<BigInt>::default();
+        assert_eq!("seeds:\n", empty.to_string());
+        assert_eq!(empty, parse_str_as(&empty.to_string()).unwrap());
+    }
+
+    /// Lowest location of the seeds and of the seed ranges in the example
+    fn lowest_locations<T: Value>() -> (T, T) {
+        let seeds = parse_str_as::<T>(example()).unwrap();
+        let ranges = parse_str_with::<T>(example(), SeedFormat::Pairs).unwrap();
+        (
+            seeds.lowest_location().unwrap().location,
+            ranges.lowest_location().unwrap().location,
+        )
+    }
+
+    #[test]
+    fn value_types() {
+        assert_eq!((35, 46), lowest_locations::<i64>());
+        assert_eq!((35, 46), lowest_locations::<u64>());
+        assert_eq!((35, 46), lowest_locations::<i128>());
+        assert_eq!(
+            (BigInt::from(35), BigInt::from(46)),
+            lowest_locations::<BigInt>()
+        );
+
+        // Negative numbers are no unsigned values
+        assert!(matches!(
+            parse_str_as::<u64>(&example().replace("52 50 48", "52 -50 48")).unwrap_err(),
+            ParseError::InvalidNumber { line: 5, .. }
+        ));
+
+        // Far beyond any machine integer
+        let huge = "1".repeat(30);
+        let almanac = parse_str_with::<BigInt>(
+            &format!("seeds: {huge}0 5\n\nseed-to-location map:\n7 {huge}1 2"),
+            SeedFormat::Pairs,
+        )
+        .unwrap();
+        assert_eq!(BigInt::from(7), almanac.lowest_location().unwrap().location);
+    }
+
+    #[test]
+    fn overflow() {
+        let mut map = AlmanacMap::<u64>::new();
+        map.insert(u64::MAX - 10, 10, 0).unwrap();
+        map.insert(0, 10, u64::MAX - 10).unwrap();
+        assert_eq!(9, map.get(&(u64::MAX - 1)));
+        assert_eq!(u64::MAX, map.get(&u64::MAX));
+        assert_eq!(
+            Err(IntervalError::Overflow {
+                start: u64::MAX - 10,
+                dest: 20,
+                length: 11
+            }),
+            AlmanacMap::new().insert(u64::MAX - 10, 11, 20)
         );
-        let location_starts = locations.iter().map(|lr| lr.0).collect::<Vec<_>>();
-        let min_loc = location_starts.iter().min().unwrap();
-        assert_eq!(&46, min_loc);
+        assert!(matches!(
+            AlmanacMap::<u64>::new().insert(20, 11, u64::MAX - 10),
+            Err(IntervalError::Overflow { .. })
+        ));
+
+        let lines = example().replace("52 50 48", "9223372036854775800 50 48");
+        assert!(matches!(
+            parse_str(&lines).unwrap_err(),
+            ParseError::Unexpected {
+                line: 5,
+                expected: "interval ending within the number type",
+                ..
+            }
+        ));
+        let lines = example().replace("52 50 48", "52 50 -48");
+        assert!(matches!(
+            parse_str(&lines).unwrap_err(),
+            ParseError::Unexpected {
+                line: 5,
+                expected: "non-negative length",
+                ..
+            }
+        ));
+
+        // Seed ranges running past the largest value
+        let lines = example().replace("seeds: 79 14", "seeds: 18446744073709551610 14");
+        assert!(parse_str_as::<u64>(&lines).unwrap().locations().is_ok());
+        let almanac = parse_str_with::<u64>(&lines, SeedFormat::Pairs).unwrap();
+        assert!(almanac.locations().is_err());
+        assert!(almanac.lowest_location().is_err());
+    }
+
+    /// A map of up to four source ranges that do not overlap but come in any order
+    fn any_map() -> impl Strategy<Value = AlmanacMap> {
+        vec((0..4_i64, 1..6_i64, 0..40_i64), 0..4)
+            .prop_map(|ranges| {
+                let mut start = 0;
+                let mut intervals = Vec::new();
+                for (gap, length, dest) in ranges {
+                    start += gap;
+                    intervals.push(AlmanacInterval {
+                        start,
+                        dest,
+                        length,
+                    });
+                    start += length;
+                }
+                intervals
+            })
+            .prop_shuffle()
+            .prop_map(|ranges| {
+                let mut map = AlmanacMap::new();
+                for i in ranges {
+                    map.insert(i.start, i.length, i.dest).unwrap();
+                }
+                map
+            })
+    }
+
+    /// Seven random maps chained from seed to location like in the puzzle
+    fn any_almanac() -> impl Strategy<Value = Almanac> {
+        (vec((0..40_i64, 0..8_i64), 0..4), vec(any_map(), 7)).prop_map(|(seeds, maps)| {
+            let categories = ["seed", "a", "b", "c", "d", "e", "f", "location"];
+            let stages = maps
+                .into_iter()
+                .enumerate()
+                .map(|(i, map)| AlmanacStage {
+                    source: categories[i].to_string(),
+                    destination: categories[i + 1].to_string(),
+                    map,
+                })
+                .collect();
+            Almanac {
+                seeds: SeedSpec::Pairs(seeds),
+                stages,
+            }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn ranges_agree_with_every_seed(almanac in any_almanac()) {
+            let expanded = almanac.seeds.ranges().unwrap().into_iter().flatten().collect::<Vec<_>>();
+            let every_seed = expanded
+                .iter()
+                .map(|seed| almanac.convert(SEED, LOCATION, *seed).unwrap())
+                .collect::<BTreeSet<_>>();
+            let ranges = almanac
+                .locations()
+                .unwrap()
+                .into_iter()
+                .flatten()
+                .collect::<BTreeSet<_>>();
+            prop_assert_eq!(&every_seed, &ranges);
+
+            let listed = Almanac {
+                seeds: SeedSpec::Individual(expanded),
+                ..almanac.clone()
+            };
+            match (listed.lowest_location(), almanac.lowest_location()) {
+                (Ok(listed), Ok(lowest)) => {
+                    prop_assert_eq!(&listed, &lowest);
+                    prop_assert_eq!(
+                        lowest.location,
+                        almanac.convert(SEED, LOCATION, lowest.seed).unwrap()
+                    );
+                }
+                (listed, lowest) => prop_assert!(listed.is_err() && lowest.is_err()),
+            }
+        }
+
+        #[test]
+        fn written_almanacs_parse_back(almanac in any_almanac()) {
+            let text = almanac.to_string();
+            prop_assert_eq!(&almanac, &parse_str_with(&text, almanac.seeds.format()).unwrap());
+        }
+
+        #[test]
+        fn unit_ranges_agree_with_get(map in any_map(), src in -5..60_i64) {
+            prop_assert_eq!(set(&[(map.get(&src), 1)]), map.get_ranges(&set(&[(src, 1)])));
+        }
+
+        #[test]
+        fn composition_agrees_with_lookups(first in any_map(), then in any_map(), src in -5..60_i64) {
+            let composed = first.compose(&then);
+            prop_assert_eq!(then.get(&first.get(&src)), composed.get(&src));
+            prop_assert_eq!(&composed, &composed.normalized());
+        }
+
+        #[test]
+        fn preimage_agrees_with_lookups(map in any_map(), start in -5..60_i64, length in 0..10_i64) {
+            let sources = map
+                .preimage(&set(&[(start, length)]))
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            let expected = (-10..100)
+                .filter(|src| (start..start + length).contains(&map.get(src)))
+                .collect::<Vec<_>>();
+            prop_assert_eq!(expected, sources);
+        }
+
+        #[test]
+        fn inverse_undoes_map(map in any_map(), src in -5..60_i64) {
+            if let Ok(inverse) = map.invert() {
+                prop_assert_eq!(src, inverse.get(&map.get(&src)));
+                prop_assert_eq!(src, map.get(&inverse.get(&src)));
+            }
+        }
     }
 }
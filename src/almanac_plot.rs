@@ -0,0 +1,199 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::Result;
+use num::ToPrimitive;
+use std::io::Write;
+
+use crate::almanac::{Almanac, AlmanacMap, Value, LOCATION, SEED};
+use crate::interval_set::IntervalSet;
+
+/// Side length of the square a map is drawn in
+const SIZE: f64 = 240.0;
+/// Space around each square for its title and axis labels
+const MARGIN: f64 = 40.0;
+const COLUMNS: usize = 4;
+
+/// One map drawn as a function from its source to its destination category
+struct Panel<'a, T> {
+    title: String,
+    map: &'a AlmanacMap<T>,
+    /// Values the seed ranges take in the source category
+    shaded: IntervalSet<T>,
+    /// Source value and result of the lowest location reached from the seed ranges
+    lowest: Option<(T, T)>,
+}
+
+impl<T: Value + ToPrimitive> Panel<'_, T> {
+    /// Smallest and largest value shown on both axes
+    fn bounds(&self) -> (T, T) {
+        let mut values = Vec::new();
+        for i in self.map.intervals() {
+            values.extend([i.start.clone(), i.end(), i.dest.clone(), i.dest_end()]);
+        }
+        for range in &self.shaded {
+            values.extend([range.start.clone(), range.end.clone()]);
+        }
+        match (values.iter().min(), values.iter().max()) {
+            (Some(low), Some(high)) if low < high => (low.clone(), high.clone()),
+            _ => (T::zero(), T::one()),
+        }
+    }
+
+    fn write<W: Write>(&self, out: &mut W, left: f64, top: f64) -> Result<()> {
+        let (low, high) = self.bounds();
+        // Values too large for an `f64` are only drawn approximately
+        let coordinate = |value: &T| value.to_f64().unwrap_or(f64::MAX);
+        let (first, span) = (coordinate(&low), coordinate(&high) - coordinate(&low));
+        let scale = |value: &T| (coordinate(value) - first) / span * SIZE;
+        let x = |value: &T| left + scale(value);
+        let y = |value: &T| top + SIZE - scale(value);
+
+        writeln!(out, "  <g>")?;
+        writeln!(
+            out,
+            r#"    <text x="{:.1}" y="{:.1}" font-size="14">{}</text>"#,
+            left,
+            top - 12.0,
+            self.title
+        )?;
+        for range in &self.shaded {
+            writeln!(
+                out,
+                r##"    <rect x="{:.1}" y="{top:.1}" width="{:.1}" height="{SIZE:.1}" fill="#f4a300" fill-opacity="0.25"/>"##,
+                x(&range.start),
+                scale(&range.end) - scale(&range.start)
+            )?;
+        }
+        writeln!(
+            out,
+            r#"    <rect x="{left:.1}" y="{top:.1}" width="{SIZE:.1}" height="{SIZE:.1}" fill="none" stroke="black"/>"#
+        )?;
+        for (start, end, interval) in self.map.pieces(low.clone(), high.clone()) {
+            let (dest_start, dest_end) = match interval {
+                Some(i) => (i.translate(&start), i.translate(&end)),
+                None => (start.clone(), end.clone()),
+            };
+            let colour = if interval.is_some() {
+                r##"stroke="#1f5fbf" stroke-width="2""##
+            } else {
+                r##"stroke="#888888" stroke-dasharray="4 3""##
+            };
+            writeln!(
+                out,
+                r#"    <line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" {colour}/>"#,
+                x(&start),
+                y(&dest_start),
+                x(&end),
+                y(&dest_end)
+            )?;
+        }
+        if let Some((source, location)) = &self.lowest {
+            writeln!(
+                out,
+                r#"    <circle cx="{:.1}" cy="{:.1}" r="4" fill="red"><title>{source} to {location}</title></circle>"#,
+                x(source),
+                y(location)
+            )?;
+        }
+        writeln!(
+            out,
+            r#"    <text x="{left:.1}" y="{:.1}" font-size="10">{low}</text>"#,
+            top + SIZE + 14.0
+        )?;
+        writeln!(
+            out,
+            r#"    <text x="{:.1}" y="{:.1}" font-size="10" text-anchor="end">{high}</text>"#,
+            left + SIZE,
+            top + SIZE + 14.0
+        )?;
+        writeln!(out, "  </g>")?;
+        Ok(())
+    }
+}
+
+/// Writes a standalone SVG plotting every map of the almanac and the whole chain from
+/// seed to location as functions. The values the seeds take are shaded on the input
+/// axis and the lowest location they reach is marked.
+pub fn write_svg<W: Write, T: Value + ToPrimitive>(mut out: W, almanac: &Almanac<T>) -> Result<()> {
+    let seeds = almanac.seeds.ranges()?;
+    let mut panels = Vec::new();
+    for stage in &almanac.stages {
+        panels.push(Panel {
+            title: format!("{}-to-{}", stage.source, stage.destination),
+            map: &stage.map,
+            shaded: almanac
+                .convert_ranges(SEED, &stage.source, &seeds)
+                .unwrap_or_default(),
+            lowest: None,
+        });
+    }
+    let chain = almanac.collapse(SEED, LOCATION).ok();
+    if let Some(map) = &chain {
+        let lowest = almanac
+            .lowest_location()
+            .ok()
+            .map(|lowest| (lowest.seed, lowest.location));
+        panels.push(Panel {
+            title: format!("{SEED}-to-{LOCATION}"),
+            map,
+            shaded: seeds.clone(),
+            lowest,
+        });
+    }
+
+    let cell = SIZE + 2.0 * MARGIN;
+    let columns = panels.len().clamp(1, COLUMNS);
+    let rows = panels.len().div_ceil(COLUMNS).max(1);
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0:.0}" height="{1:.0}" viewBox="0 0 {0:.0} {1:.0}" font-family="sans-serif">"#,
+        cell * columns as f64,
+        cell * rows as f64
+    )?;
+    writeln!(out, r#"  <rect width="100%" height="100%" fill="white"/>"#)?;
+    for (i, panel) in panels.iter().enumerate() {
+        let left = (i % COLUMNS) as f64 * cell + MARGIN;
+        let top = (i / COLUMNS) as f64 * cell + MARGIN;
+        panel.write(&mut out, left, top)?;
+    }
+    writeln!(out, "</svg>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::almanac::{example, parse_str_with, SeedFormat};
+
+    fn render<T: Value + ToPrimitive>(almanac: &Almanac<T>) -> String {
+        let mut svg = Vec::new();
+        write_svg(&mut svg, almanac).unwrap();
+        String::from_utf8(svg).unwrap()
+    }
+
+    #[test]
+    fn plot_web() {
+        let svg = render(&parse_str_with::<i64>(example(), SeedFormat::Pairs).unwrap());
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(8, svg.matches("<g>").count());
+        assert_eq!(8, svg.matches("</g>").count());
+        assert!(svg.contains(">seed-to-soil</text>"));
+        assert!(svg.contains(">seed-to-location</text>"));
+        // Each panel shades the two seed ranges, or the pieces earlier stages split them in
+        assert!(svg.matches(r#"fill-opacity="0.25""#).count() >= 16);
+        assert!(svg.contains("<title>82 to 46</title>"));
+
+        // The same picture whatever type holds the values
+        let unsigned = parse_str_with::<u64>(example(), SeedFormat::Pairs).unwrap();
+        assert_eq!(svg, render(&unsigned));
+    }
+
+    #[test]
+    fn plot_empty() {
+        let svg = render(&Almanac::<i64>::default());
+        assert!(svg.contains(r#"width="320""#));
+        assert_eq!(0, svg.matches("<g>").count());
+    }
+}
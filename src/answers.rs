@@ -0,0 +1,240 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::input::InputSource;
+use crate::solution::{Day, Part};
+
+/// Expected answers, read from a TOML file with one `[dayNN]` table per day holding
+/// `part1` and `part2`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    expected: HashMap<(u8, Part), String>,
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Answers> {
+        let table = text.parse::<toml::Table>()?;
+        let mut expected = HashMap::new();
+        for (key, parts) in table {
+            let day = key
+                .strip_prefix("day")
+                .and_then(|d| d.parse::<u8>().ok())
+                .with_context(|| format!("Invalid day {key:?}, expected e.g. \"day05\""))?;
+            let parts = parts
+                .as_table()
+                .with_context(|| format!("{key} is not a table"))?;
+            for (name, value) in parts {
+                let part = match name.as_str() {
+                    "part1" => Part::One,
+                    "part2" => Part::Two,
+                    _ => bail!("Invalid part {name:?} in {key}, expected part1 or part2"),
+                };
+                let value = match value {
+                    toml::Value::String(s) => s.clone(),
+                    toml::Value::Integer(i) => i.to_string(),
+                    _ => bail!("Answer for {key}.{name} is neither a string nor an integer"),
+                };
+                expected.insert((day, part), value);
+            }
+        }
+        Ok(Answers { expected })
+    }
+
+    pub fn load(path: &Path) -> Result<Answers> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        Answers::parse(&text).with_context(|| format!("Could not parse {}", path.display()))
+    }
+
+    /// `answers.toml` next to `Cargo.toml`
+    pub fn default_path() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("answers.toml")
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Option<&str> {
+        self.expected.get(&(day, part)).map(|s| s.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail {
+        expected: String,
+        actual: String,
+    },
+    /// Solving the day returned an error
+    Error(String),
+    MissingInput,
+    MissingAnswer {
+        actual: String,
+    },
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Status::Pass => write!(f, "pass"),
+            Status::Fail { expected, actual } => {
+                write!(f, "FAIL (expected {expected}, got {actual})")
+            }
+            Status::Error(e) => write!(f, "ERROR ({e})"),
+            Status::MissingInput => write!(f, "missing input"),
+            Status::MissingAnswer { actual } => write!(f, "missing answer (got {actual})"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    pub day: u8,
+    pub part: Part,
+    pub status: Status,
+}
+
+impl CheckResult {
+    pub fn is_failure(&self) -> bool {
+        matches!(self.status, Status::Fail { .. } | Status::Error(_))
+    }
+}
+
+/// Solves `parts` of `day` and compares them against the expected answers
+pub fn check_day(
+    day: &Day,
+    parts: &[Part],
+    input: &InputSource,
+    answers: &Answers,
+) -> Vec<CheckResult> {
+    let result = |part: Part, status: Status| CheckResult {
+        day: day.day,
+        part,
+        status,
+    };
+
+    let mut input = match input.open(day.day) {
+        Ok(input) => input,
+        Err(e) => {
+            // Only a day without its usual input file is missing, a path given explicitly
+            // has to exist
+            let not_found = e
+                .chain()
+                .filter_map(|cause| cause.downcast_ref::<io::Error>())
+                .any(|cause| cause.kind() == io::ErrorKind::NotFound);
+            let status = if *input == InputSource::Default && not_found {
+                Status::MissingInput
+            } else {
+                Status::Error(format!("{e:#}"))
+            };
+            return parts.iter().map(|p| result(*p, status.clone())).collect();
+        }
+    };
+    let solved = match (day.run)(&mut input, parts) {
+        Ok(solved) => solved,
+        Err(e) => {
+            return parts
+                .iter()
+                .map(|p| result(*p, Status::Error(e.to_string())))
+                .collect()
+        }
+    };
+
+    solved
+        .answers
+        .into_iter()
+        .map(|answer| {
+            let status = match answers.get(day.day, answer.part) {
+                Some(expected) if expected == answer.value => Status::Pass,
+                Some(expected) => Status::Fail {
+                    expected: expected.to_string(),
+                    actual: answer.value,
+                },
+                None => Status::MissingAnswer {
+                    actual: answer.value,
+                },
+            };
+            result(answer.part, status)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input;
+    use crate::solution;
+
+    fn example() -> &'static str {
+        r#"
+[day01]
+part1 = 142
+part2 = "281"
+
+[day03]
+part2 = 467835
+"#
+    }
+
+    #[test]
+    fn parse_answers() {
+        let answers = Answers::parse(example()).unwrap();
+        assert_eq!(Some("142"), answers.get(1, Part::One));
+        assert_eq!(Some("281"), answers.get(1, Part::Two));
+        assert_eq!(None, answers.get(3, Part::One));
+        assert_eq!(Some("467835"), answers.get(3, Part::Two));
+
+        assert!(Answers::parse("[day1]\npart3 = 1").is_err());
+        assert!(Answers::parse("[first]\npart1 = 1").is_err());
+        assert!(Answers::parse("[day01]\npart1 = 1.5").is_err());
+    }
+
+    #[test]
+    fn check_missing_input() {
+        let day = solution::day(1).unwrap();
+        let input = InputSource::Path(PathBuf::from("/nonexistent/input-01.txt"));
+        let results = check_day(&day, &Part::all(), &input, &Answers::default());
+        assert_eq!(2, results.len());
+        assert!(results.iter().all(
+            |r| matches!(&r.status, Status::Error(e) if e.contains("/nonexistent/input-01.txt"))
+        ));
+
+        // Only days without an input in the repository can be missing their default one
+        let day = solution::days()
+            .into_iter()
+            .find(|day| !input::default_path(day.day).exists());
+        if let Some(day) = day {
+            let results = check_day(
+                &day,
+                &Part::all(),
+                &InputSource::Default,
+                &Answers::default(),
+            );
+            assert!(results.iter().all(|r| r.status == Status::MissingInput));
+        }
+    }
+
+    /// Guards refactorings: every day with an input in the repository has to reproduce
+    /// its recorded answers.
+    #[test]
+    fn repository_answers() {
+        let answers = Answers::load(&Answers::default_path()).unwrap();
+        let mut results = Vec::new();
+        for day in solution::days() {
+            results.extend(check_day(
+                &day,
+                &Part::all(),
+                &InputSource::Default,
+                &answers,
+            ));
+        }
+        for result in results {
+            assert!(!result.is_failure(), "{:?}", result);
+        }
+    }
+}
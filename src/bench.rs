@@ -0,0 +1,170 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::{bail, Result};
+use std::fmt::{self, Display};
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::solution::{Day, Part};
+
+/// What is being timed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    Parse,
+    Part(Part),
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Part(part) => write!(f, "part{}", part),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// `None` if there are no samples. For an even number of samples the median is the
+    /// mean of the two middle ones.
+    pub fn of(samples: &[Duration]) -> Option<Stats> {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let min = *sorted.first()?;
+        let max = *sorted.last()?;
+        let middle = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        } else {
+            sorted[middle]
+        };
+        Some(Stats {
+            runs: sorted.len(),
+            min,
+            median,
+            max,
+        })
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "min {:?}, median {:?}, max {:?} ({} runs)",
+            self.min, self.median, self.max, self.runs
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    pub day: u8,
+    pub name: &'static str,
+    pub stage: Stage,
+    pub stats: Stats,
+}
+
+/// Solves `parts` of `day` `runs` times, timing parsing and every part separately
+pub fn bench_day(day: &Day, text: &str, parts: &[Part], runs: usize) -> Result<Vec<Measurement>> {
+    if runs == 0 {
+        bail!("Need at least one run");
+    }
+    let mut parse_times = Vec::with_capacity(runs);
+    let mut part_times = vec![Vec::with_capacity(runs); parts.len()];
+    for _ in 0..runs {
+        let solved = (day.run)(&mut text.as_bytes(), parts)?;
+        parse_times.push(solved.parse_time);
+        for (times, answer) in part_times.iter_mut().zip(solved.answers) {
+            times.push(answer.time);
+        }
+    }
+
+    let stages = std::iter::once(Stage::Parse).chain(parts.iter().map(|p| Stage::Part(*p)));
+    let samples = std::iter::once(parse_times).chain(part_times);
+    Ok(stages
+        .zip(samples)
+        .map(|(stage, samples)| Measurement {
+            day: day.day,
+            name: day.name,
+            stage,
+            stats: Stats::of(&samples).expect("at least one run"),
+        })
+        .collect())
+}
+
+/// Writes the measurements as CSV, durations in nanoseconds
+pub fn write_csv<W: Write>(mut out: W, measurements: &[Measurement]) -> io::Result<()> {
+    writeln!(out, "day,name,stage,runs,min_ns,median_ns,max_ns")?;
+    for m in measurements {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{}",
+            m.day,
+            m.name,
+            m.stage,
+            m.stats.runs,
+            m.stats.min.as_nanos(),
+            m.stats.median.as_nanos(),
+            m.stats.max.as_nanos()
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution;
+
+    fn ms(millis: &[u64]) -> Vec<Duration> {
+        millis.iter().map(|m| Duration::from_millis(*m)).collect()
+    }
+
+    #[test]
+    fn stats() {
+        assert_eq!(None, Stats::of(&[]));
+        assert_eq!(
+            Some(Stats {
+                runs: 3,
+                min: Duration::from_millis(1),
+                median: Duration::from_millis(4),
+                max: Duration::from_millis(9),
+            }),
+            Stats::of(&ms(&[9, 1, 4]))
+        );
+        assert_eq!(
+            Duration::from_millis(3),
+            Stats::of(&ms(&[4, 1, 2, 8])).unwrap().median
+        );
+    }
+
+    #[test]
+    fn bench_and_summary() {
+        let day = solution::day(1).unwrap();
+        let text = "1abc2\npqr3stu8vwx";
+        let measurements = bench_day(&day, text, &Part::all(), 3).unwrap();
+        assert_eq!(
+            vec![Stage::Parse, Stage::Part(Part::One), Stage::Part(Part::Two)],
+            measurements.iter().map(|m| m.stage).collect::<Vec<_>>()
+        );
+        assert!(measurements.iter().all(|m| m.stats.runs == 3));
+        assert!(bench_day(&day, text, &Part::all(), 0).is_err());
+
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &measurements).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(4, lines.len());
+        assert_eq!("day,name,stage,runs,min_ns,median_ns,max_ns", lines[0]);
+        assert!(lines[2].starts_with("1,trebuchet,part1,3,"));
+    }
+}
@@ -2,22 +2,11 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use anyhow::Result;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
+use std::env;
+use std::process::ExitCode;
 
-use advent_of_code_2023::trebuchet::*;
+use advent_of_code_2023::runner;
 
-fn main() -> Result<()> {
-    let f = File::open("input-01.txt")?;
-    let reader = BufReader::new(f);
-
-    let lines = reader.lines().collect::<Result<Vec<_>, _>>().unwrap();
-    let sum = count(&lines);
-
-    let lines = preprocess(lines);
-    println!("part 1: {:?}", sum);
-    println!("part 2: {:?}", count(&lines));
-
-    Ok(())
+fn main() -> Result<ExitCode> {
+    runner::main_for_day(1, env::args().skip(1))
 }
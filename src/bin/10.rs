@@ -2,21 +2,11 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use anyhow::Result;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
+use std::env;
+use std::process::ExitCode;
 
-use advent_of_code_2023::pipe_maze::*;
+use advent_of_code_2023::runner;
 
-fn main() -> Result<()> {
-    let f = File::open("input-10.txt")?;
-    let reader = BufReader::new(f);
-
-    let lines = reader.lines().collect::<Result<Vec<_>, _>>().unwrap();
-
-    let mut maze = parse(&lines).unwrap();
-    println!("part 1: {}", maze.furthest_distance().unwrap());
-    println!("part 2: {}", maze.count_inside(&lines).unwrap());
-
-    Ok(())
+fn main() -> Result<ExitCode> {
+    runner::main_for_day(10, env::args().skip(1))
 }
@@ -2,36 +2,11 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use anyhow::Result;
-use rayon::prelude::*;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
+use std::env;
+use std::process::ExitCode;
 
-use advent_of_code_2023::hot_springs::*;
+use advent_of_code_2023::runner;
 
-fn main() -> Result<()> {
-    let f = File::open("input-12.txt")?;
-    let reader = BufReader::new(f);
-
-    let lines = reader.lines().collect::<Result<Vec<_>, _>>().unwrap();
-
-    let mut springs = parse(&lines, false).expect("problem parsing");
-    println!(
-        "part 1: {}",
-        springs
-            .par_iter_mut()
-            .map(|s| s.get_arrangements())
-            .sum::<u64>()
-    );
-
-    let mut springs = parse(&lines, true).expect("problem parsing");
-    println!(
-        "part 1: {}",
-        springs
-            .par_iter_mut()
-            .map(|s| s.get_arrangements())
-            .sum::<u64>()
-    );
-
-    Ok(())
+fn main() -> Result<ExitCode> {
+    runner::main_for_day(12, env::args().skip(1))
 }
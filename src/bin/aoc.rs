@@ -0,0 +1,12 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::Result;
+use std::env;
+use std::process::ExitCode;
+
+use advent_of_code_2023::runner;
+
+fn main() -> Result<ExitCode> {
+    runner::main(env::args().skip(1))
+}
@@ -3,10 +3,15 @@
 
 // 2023-06
 
-use anyhow::{Ok, Result};
+use anyhow::Result;
+use std::io::BufRead;
 
 use rayon::prelude::*;
 
+use crate::error::{InputLine, ParseResult};
+use crate::input::LineReader;
+use crate::solution::Solution;
+
 #[derive(Debug, Clone)]
 pub struct Race {
     pub time: i64,
@@ -28,19 +33,45 @@ impl Race {
     }
 }
 
-pub fn parse(lines: &[String]) -> Result<Vec<Race>> {
-    let times: Vec<i32> = lines[0]
-        .split(' ')
-        .skip(1)
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse::<i32>().expect("Could not parse i32"))
-        .collect();
-    let distances: Vec<i32> = lines[1]
+/// The times and the distances line, the only two lines of the input
+fn race_lines<R: BufRead>(input: R) -> ParseResult<[String; 2]> {
+    let mut lines = LineReader::new(Day06::DAY, input);
+    let times = match lines.next_line()? {
+        Some(line) => line.text.to_string(),
+        None => return Err(lines.missing("times")),
+    };
+    let distances = match lines.next_line()? {
+        Some(line) => line.text.to_string(),
+        None => return Err(lines.missing("distances")),
+    };
+    Ok([times, distances])
+}
+
+fn numbers(line: &InputLine) -> ParseResult<Vec<i32>> {
+    line.text
         .split(' ')
         .skip(1)
         .filter(|s| !s.is_empty())
-        .map(|s| s.parse::<i32>().expect("Could not parse i32"))
-        .collect();
+        .map(|s| line.number::<i32>(s))
+        .collect()
+}
+
+/// The single number on a line when ignoring the spaces in it
+fn kerned_number(line: &InputLine) -> ParseResult<i64> {
+    let (_, digits) = line.require(line.text.split_once(':'), "':'")?;
+    digits
+        .replace(' ', "")
+        .parse::<i64>()
+        .map_err(|_| line.unexpected(digits.trim(), "number"))
+}
+
+fn races(lines: &[String; 2]) -> ParseResult<Vec<Race>> {
+    let distances_line = InputLine::new(Day06::DAY, 1, &lines[1]);
+    let times = numbers(&InputLine::new(Day06::DAY, 0, &lines[0]))?;
+    let distances = numbers(&distances_line)?;
+    if times.len() != distances.len() {
+        return Err(distances_line.missing("as many distances as times"));
+    }
     let result: Vec<Race> = times
         .iter()
         .zip(distances.iter())
@@ -52,57 +83,103 @@ pub fn parse(lines: &[String]) -> Result<Vec<Race>> {
     Ok(result)
 }
 
-pub fn parse_p2(lines: &[String]) -> Result<Vec<Race>> {
-    let times: Vec<i64> = lines[0]
-        .replace(' ', "")
-        .split(':')
-        .skip(1)
-        .map(|s| s.parse::<i64>().expect("Could not parse i32"))
-        .collect();
-    let distances: Vec<i64> = lines[1]
-        .replace(' ', "")
-        .split(':')
-        .skip(1)
-        .map(|s| s.parse::<i64>().expect("Could not parse i32"))
-        .collect();
-    let result: Vec<Race> = times
-        .iter()
-        .zip(distances.iter())
-        .map(|(t, d)| Race {
-            time: *t,
-            distance: *d,
-        })
-        .collect();
-    Ok(result)
+fn kerned_race(lines: &[String; 2]) -> ParseResult<Vec<Race>> {
+    let time = kerned_number(&InputLine::new(Day06::DAY, 0, &lines[0]))?;
+    let distance = kerned_number(&InputLine::new(Day06::DAY, 1, &lines[1]))?;
+    Ok(vec![Race { time, distance }])
+}
+
+pub fn parse<R: BufRead>(input: R) -> ParseResult<Vec<Race>> {
+    races(&race_lines(input)?)
+}
+
+pub fn parse_p2<R: BufRead>(input: R) -> ParseResult<Vec<Race>> {
+    kerned_race(&race_lines(input)?)
+}
+
+pub fn parse_str(text: &str) -> ParseResult<Vec<Race>> {
+    parse(text.as_bytes())
+}
+
+pub fn parse_p2_str(text: &str) -> ParseResult<Vec<Race>> {
+    parse_p2(text.as_bytes())
+}
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    const NAME: &'static str = "boat_race";
+
+    type Input = (Vec<Race>, Vec<Race>);
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {
+        let lines = race_lines(input)?;
+        Ok((races(&lines)?, kerned_race(&lines)?))
+    }
+
+    fn part1(input: &Self::Input) -> Result<i64> {
+        Ok(input.0.iter().map(|r| r.winning_strategies()).product())
+    }
+
+    fn part2(input: &Self::Input) -> Result<i64> {
+        Ok(input.1.iter().map(|r| r.winning_strategies()).product())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ParseError;
 
-    fn example() -> Vec<String> {
+    fn example() -> &'static str {
         r#"Time:      7  15   30
 Distance:  9  40  200"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
     #[test]
     fn result_web() {
-        let races = parse(&example()).unwrap();
+        let races = parse_str(example()).unwrap();
         assert_eq!(4_i64, races[0].winning_strategies());
         assert_eq!(8_i64, races[1].winning_strategies());
         assert_eq!(9_i64, races[2].winning_strategies());
         assert_eq!(
             288_i64,
-            races.iter().map(|r| r.winning_strategies()).product()
+            races
+                .iter()
+                .map(|r| r.winning_strategies())
+                .product::<i64>()
         );
     }
 
     #[test]
     fn result_web2() {
-        let races = parse_p2(&example()).unwrap();
+        let races = parse_p2_str(example()).unwrap();
         assert_eq!(71503, races[0].winning_strategies());
     }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            ParseError::MissingLine {
+                day: 6,
+                line: 2,
+                expected: "distances"
+            },
+            parse_str("Time:      7  15   30\n").unwrap_err()
+        );
+        let lines = "Time:      7  15   30\nDistance:  9  4o  200";
+        assert_eq!(
+            ParseError::InvalidNumber {
+                day: 6,
+                line: 2,
+                column: 15,
+                found: "4o".to_string()
+            },
+            parse_str(lines).unwrap_err()
+        );
+        assert!(parse_p2_str(lines).is_err());
+    }
 }
@@ -3,9 +3,14 @@
 
 // 2023-07
 
-use anyhow::{Ok, Result};
+use anyhow::Result;
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::io::BufRead;
+
+use crate::error::{InputLine, ParseResult};
+use crate::input::LineReader;
+use crate::solution::Solution;
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Type {
@@ -49,7 +54,10 @@ impl PartialEq for Hand {
 }
 
 impl Hand {
-    pub fn new(hand: String, bid: i32) -> Hand {
+    /// Fails on anything but five known cards. `hand` is the token of `line` the errors
+    /// point to.
+    pub fn new(line: &InputLine, hand: &str, bid: i32) -> ParseResult<Hand> {
+        let strength = Hand::determine_strength_p1(line, hand)?;
         let mut cards = HashMap::new();
         for character in hand.chars().collect::<Vec<_>>() {
             let card = cards.entry(character).or_insert(0);
@@ -57,17 +65,17 @@ impl Hand {
         }
 
         let hand_type = Hand::determine_type_p1(&cards);
-        let strength = Hand::determine_strength_p1(&hand);
 
-        Hand {
-            hand,
+        Ok(Hand {
+            hand: hand.to_string(),
             hand_type,
             bid,
             strength,
-        }
+        })
     }
 
-    pub fn new_p2(hand: String, bid: i32) -> Hand {
+    pub fn new_p2(line: &InputLine, hand: &str, bid: i32) -> ParseResult<Hand> {
+        let strength = Hand::determine_strength_p2(line, hand)?;
         let mut cards = HashMap::new();
         for character in hand.chars().collect::<Vec<_>>() {
             let card = cards.entry(character).or_insert(0);
@@ -75,14 +83,13 @@ impl Hand {
         }
 
         let hand_type = Hand::determine_type_p2(&cards);
-        let strength = Hand::determine_strength_p2(&hand);
 
-        Hand {
-            hand,
+        Ok(Hand {
+            hand: hand.to_string(),
             hand_type,
             bid,
             strength,
-        }
+        })
     }
 
     fn determine_type_p1(cards: &HashMap<char, i32>) -> Type {
@@ -155,42 +162,38 @@ impl Hand {
         Hand::determine_type_p1(&cards)
     }
 
-    fn determine_strength_p1(hand: &str) -> i64 {
-        let mut result: i64 = 0;
-        for (i, chara) in hand.char_indices() {
-            let value: i64 = match chara.to_digit(10) {
-                Some(x) => x as i64,
-                None => match chara {
-                    'A' => 14,
-                    'K' => 13,
-                    'Q' => 12,
-                    'J' => 11,
-                    'T' => 10,
-                    _ => todo!("Unknown card"),
-                },
-            };
-            result += value * 100_i64.pow((6 - i).try_into().expect("error in exponent"));
-        }
-        result
+    fn determine_strength_p1(line: &InputLine, hand: &str) -> ParseResult<i64> {
+        Hand::determine_strength(line, hand, 11)
     }
 
-    fn determine_strength_p2(hand: &str) -> i64 {
+    fn determine_strength_p2(line: &InputLine, hand: &str) -> ParseResult<i64> {
+        Hand::determine_strength(line, hand, 1)
+    }
+
+    /// Orders hands of the same type card by card, with `jack` as the value of J
+    fn determine_strength(line: &InputLine, hand: &str, jack: i64) -> ParseResult<i64> {
+        if hand.chars().count() != 5 {
+            return Err(line.unexpected(hand, "hand of five cards"));
+        }
         let mut result: i64 = 0;
-        for (i, chara) in hand.char_indices() {
-            let value: i64 = match chara.to_digit(10) {
-                Some(x) => x as i64,
-                None => match chara {
-                    'A' => 14,
-                    'K' => 13,
-                    'Q' => 12,
-                    'J' => 1,
-                    'T' => 10,
-                    _ => todo!("Unknown card"),
-                },
-            };
-            result += value * 100_i64.pow((6 - i).try_into().expect("error in exponent"));
+        for (i, card) in hand.chars().enumerate() {
+            let value = card_value(card, jack)
+                .ok_or_else(|| line.unexpected_at(line.column_of(hand) + i, card, "card"))?;
+            result += value * 100_i64.pow(6 - i as u32);
         }
-        result
+        Ok(result)
+    }
+}
+
+fn card_value(card: char, jack: i64) -> Option<i64> {
+    match card {
+        '2'..='9' => card.to_digit(10).map(i64::from),
+        'T' => Some(10),
+        'J' => Some(jack),
+        'Q' => Some(12),
+        'K' => Some(13),
+        'A' => Some(14),
+        _ => None,
     }
 }
 
@@ -203,54 +206,141 @@ pub fn total_winnings(mut hands: Vec<Hand>) -> i64 {
     result
 }
 
-pub fn parse(lines: &Vec<String>) -> Result<Vec<Hand>> {
-    let mut result = Vec::new();
-    for line in lines {
-        let token: Vec<&str> = line.split(' ').collect();
-        result.push(Hand::new(
-            token[0].to_owned(),
-            token[1].parse::<i32>().expect("Could not parse bid"),
-        ));
+fn parse_line<'a>(line: &InputLine<'a>) -> ParseResult<(&'a str, i32)> {
+    let mut token = line.text.split(' ');
+    let hand = line.require(token.next(), "hand")?;
+    let bid = line.number::<i32>(line.require(token.next(), "bid")?)?;
+    if let Some(rest) = token.next() {
+        return Err(line.unexpected(rest, "end of line"));
     }
-    Ok(result)
+    Ok((hand, bid))
 }
 
-pub fn parse_p2(lines: &Vec<String>) -> Result<Vec<Hand>> {
-    let mut result = Vec::new();
-    for line in lines {
-        let token: Vec<&str> = line.split(' ').collect();
-        result.push(Hand::new_p2(
-            token[0].to_owned(),
-            token[1].parse::<i32>().expect("Could not parse bid"),
-        ));
+/// Hands for both parts, J being a jack in the first and a joker in the second
+fn parse_hands<R: BufRead>(input: R) -> ParseResult<(Vec<Hand>, Vec<Hand>)> {
+    let mut hands = Vec::new();
+    let mut hands_p2 = Vec::new();
+    let mut lines = LineReader::new(Day07::DAY, input);
+    while let Some(line) = lines.next_line()? {
+        let (hand, bid) = parse_line(&line)?;
+        hands.push(Hand::new(&line, hand, bid)?);
+        hands_p2.push(Hand::new_p2(&line, hand, bid)?);
+    }
+    Ok((hands, hands_p2))
+}
+
+pub fn parse<R: BufRead>(input: R) -> ParseResult<Vec<Hand>> {
+    Ok(parse_hands(input)?.0)
+}
+
+pub fn parse_p2<R: BufRead>(input: R) -> ParseResult<Vec<Hand>> {
+    Ok(parse_hands(input)?.1)
+}
+
+pub fn parse_str(text: &str) -> ParseResult<Vec<Hand>> {
+    parse(text.as_bytes())
+}
+
+pub fn parse_p2_str(text: &str) -> ParseResult<Vec<Hand>> {
+    parse_p2(text.as_bytes())
+}
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    const NAME: &'static str = "camel_cards";
+
+    type Input = (Vec<Hand>, Vec<Hand>);
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {
+        Ok(parse_hands(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i64> {
+        Ok(total_winnings(input.0.clone()))
+    }
+
+    fn part2(input: &Self::Input) -> Result<i64> {
+        Ok(total_winnings(input.1.clone()))
     }
-    Ok(result)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ParseError;
 
-    fn example() -> Vec<String> {
+    fn example() -> &'static str {
         r#"32T3K 765
 T55J5 684
 KK677 28
 KTJJT 220
 QQQJA 483"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
     #[test]
     fn result_web() {
-        let hands = parse(&example()).unwrap();
+        let hands = parse_str(example()).unwrap();
         assert_eq!(6440, total_winnings(hands));
     }
 
     #[test]
     fn result_web_p2() {
-        let hands = parse_p2(&example()).unwrap();
+        let hands = parse_p2_str(example()).unwrap();
         assert_eq!(5905, total_winnings(hands));
     }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            ParseError::InvalidNumber {
+                day: 7,
+                line: 1,
+                column: 7,
+                found: "76x".to_string()
+            },
+            parse_str("32T3K 76x").unwrap_err()
+        );
+        assert_eq!(
+            ParseError::Unexpected {
+                day: 7,
+                line: 2,
+                column: 3,
+                expected: "card",
+                found: "X".to_string()
+            },
+            parse_p2_str("32T3K 765\nT5XJ5 684").unwrap_err()
+        );
+        assert!(matches!(
+            parse_str("32T3K").unwrap_err(),
+            ParseError::Unexpected { column: 6, .. }
+        ));
+    }
+
+    #[test]
+    fn unknown_cards() {
+        let line = InputLine::new(7, 2, "32T1K 765");
+        assert_eq!(
+            ParseError::Unexpected {
+                day: 7,
+                line: 3,
+                column: 4,
+                expected: "card",
+                found: "1".to_string()
+            },
+            Hand::new_p2(&line, &line.text[..5], 765).unwrap_err()
+        );
+        let line = InputLine::new(7, 0, "32T3KA 765");
+        assert!(matches!(
+            Hand::new(&line, &line.text[..6], 765).unwrap_err(),
+            ParseError::Unexpected {
+                expected: "hand of five cards",
+                ..
+            }
+        ));
+        assert!(Hand::new_p2(&line, "", 765).is_err());
+    }
 }
@@ -3,36 +3,48 @@
 
 // 2023-11
 
-use anyhow::{Ok, Result};
+use anyhow::Result;
+use std::io::BufRead;
+
+use crate::error::{ParseError, ParseResult};
+use crate::grid::{Grid, Position};
+use crate::solution::Solution;
 
 #[derive(Debug, Clone, Default)]
 pub struct Cosmos {
-    pub galaxies: Vec<(usize, usize)>,
-    pub max_row: usize,
-    pub max_col: usize,
+    /// The observed image, `true` where there is a galaxy
+    pub image: Grid<bool>,
+    pub galaxies: Vec<Position>,
 }
 
 impl Cosmos {
     pub fn grow(&mut self, age: usize) {
-        let used_rows = self.galaxies.iter().map(|g| g.0).collect::<Vec<_>>();
-        let used_cols = self.galaxies.iter().map(|g| g.1).collect::<Vec<_>>();
-
-        for row in (0..self.max_row + 1).rev() {
-            if !used_rows.contains(&row) {
-                for (i, galaxy) in self.galaxies.clone().iter().enumerate() {
-                    if galaxy.0 >= row {
-                        self.galaxies[i].0 += age;
-                    }
+        let empty_rows = self
+            .image
+            .rows()
+            .enumerate()
+            .filter(|(_, row)| !row.contains(&true))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let empty_cols = self
+            .image
+            .columns()
+            .enumerate()
+            .filter_map(|(i, mut col)| if col.any(|g| *g) { None } else { Some(i) })
+            .collect::<Vec<_>>();
+
+        for row in empty_rows.into_iter().rev() {
+            for galaxy in self.galaxies.iter_mut() {
+                if galaxy.0 >= row {
+                    galaxy.0 += age;
                 }
             }
         }
 
-        for col in (0..self.max_col + 1).rev() {
-            if !used_cols.contains(&col) {
-                for (i, galaxy) in self.galaxies.clone().iter().enumerate() {
-                    if galaxy.1 >= col {
-                        self.galaxies[i].1 += age;
-                    }
+        for col in empty_cols.into_iter().rev() {
+            for galaxy in self.galaxies.iter_mut() {
+                if galaxy.1 >= col {
+                    galaxy.1 += age;
                 }
             }
         }
@@ -50,25 +62,59 @@ impl Cosmos {
     }
 }
 
-pub fn parse(lines: &[String]) -> Result<Cosmos> {
-    let mut result = Cosmos::default();
-    for (i, line) in lines.iter().enumerate() {
-        for (j, column) in line.chars().enumerate() {
-            if column == '#' {
-                result.galaxies.push((i, j));
-            }
-        }
-        result.max_col = line.len() - 1;
+pub fn parse<R: BufRead>(input: R) -> ParseResult<Cosmos> {
+    let image = Grid::parse(Day11::DAY, input, "galaxy or empty space", |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })?;
+    if image.height() == 0 {
+        return Err(ParseError::Empty { day: Day11::DAY });
+    }
+    let galaxies = image
+        .iter()
+        .filter(|(_, galaxy)| **galaxy)
+        .map(|(pos, _)| pos)
+        .collect();
+    Ok(Cosmos { image, galaxies })
+}
+
+pub fn parse_str(text: &str) -> ParseResult<Cosmos> {
+    parse(text.as_bytes())
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    const NAME: &'static str = "cosmic_expansion";
+
+    type Input = Cosmos;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<u64> {
+        let mut cosmos = input.clone();
+        cosmos.grow(1);
+        Ok(cosmos.sum_of_shortest_paths())
+    }
+
+    fn part2(input: &Self::Input) -> Result<u64> {
+        let mut cosmos = input.clone();
+        cosmos.grow(999999);
+        Ok(cosmos.sum_of_shortest_paths())
     }
-    result.max_row = lines.len() - 1;
-    Ok(result)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn example() -> Vec<String> {
+    fn example() -> &'static str {
         r#"...#......
 .......#..
 #.........
@@ -79,29 +125,25 @@ mod tests {
 ..........
 .......#..
 #...#....."#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
     #[test]
     fn result_web() {
-        let mut cosmos = parse(&example()).unwrap();
+        let mut cosmos = parse_str(example()).unwrap();
         cosmos.grow(1);
         assert_eq!(374, cosmos.sum_of_shortest_paths());
     }
 
     #[test]
     fn result_web10() {
-        let mut cosmos = parse(&example()).unwrap();
+        let mut cosmos = parse_str(example()).unwrap();
         cosmos.grow(9);
-        println!("{:?}", cosmos);
         assert_eq!(1030, cosmos.sum_of_shortest_paths());
     }
 
     #[test]
     fn result_web100() {
-        let mut cosmos = parse(&example()).unwrap();
+        let mut cosmos = parse_str(example()).unwrap();
         cosmos.grow(99);
         assert_eq!(8410, cosmos.sum_of_shortest_paths());
     }
@@ -3,7 +3,12 @@
 
 // 2023-02
 
-use anyhow::{Context, Ok, Result};
+use anyhow::Result;
+use std::io::BufRead;
+
+use crate::error::ParseResult;
+use crate::input::LineReader;
+use crate::solution::Solution;
 
 #[derive(Debug, Clone, Default)]
 pub struct CubeSet {
@@ -95,37 +100,33 @@ impl Game {
     }
 }
 
-pub fn parse(lines: &Vec<String>) -> Result<Vec<Game>> {
+pub fn parse<R: BufRead>(input: R) -> ParseResult<Vec<Game>> {
     let mut result = vec![];
-    for line in lines {
-        let colon = line.split(':');
-        let id = colon
-            .clone()
-            .next()
-            .context("prefix before colon not found")?
-            .split(' ')
-            .last()
-            .context("game id not found")?
-            .parse::<i32>()?;
-        let mut game = Game::new(id);
-        for full_draw in colon.last().context("draw not found")?.split(';') {
+    let mut lines = LineReader::new(Day02::DAY, input);
+    while let Some(line) = lines.next_line()? {
+        let (prefix, draws) = line.require(line.text.split_once(':'), "':'")?;
+        let id = line.require(prefix.split(' ').next_back(), "game id")?;
+        let mut game = Game::new(line.number::<i32>(id)?);
+        for full_draw in draws.split(';') {
             let mut set = CubeSet::default();
             for cube in full_draw.split(',') {
                 let mut nb_color = cube.trim().split(' ');
-                let nb = nb_color.next().context("no number found")?.parse::<i32>()?;
-                let color = nb_color.last();
+                let nb = line.require(nb_color.next(), "number of cubes")?;
+                let nb = line.number::<i32>(nb)?;
+                let color = line.require(nb_color.next_back(), "color")?;
                 match color {
-                    Some("red") => {
+                    "red" => {
                         set.red = Some(nb);
                     }
-                    Some("blue") => {
+                    "blue" => {
                         set.blue = Some(nb);
                     }
-                    Some("green") => {
+                    "green" => {
                         set.green = Some(nb);
                     }
-                    Some(_) => {}
-                    None => {}
+                    _ => {
+                        return Err(line.unexpected(color, "red, blue or green"));
+                    }
                 }
             }
             game.add_draw(set);
@@ -135,30 +136,59 @@ pub fn parse(lines: &Vec<String>) -> Result<Vec<Game>> {
     Ok(result)
 }
 
+pub fn parse_str(text: &str) -> ParseResult<Vec<Game>> {
+    parse(text.as_bytes())
+}
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    const NAME: &'static str = "cubebag";
+
+    type Input = Vec<Game>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        let part1 = CubeSet::new_part1();
+        Ok(input
+            .iter()
+            .filter(|g| g.is_possible(&part1))
+            .map(|g| g.id)
+            .sum())
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        Ok(input.iter().map(|g| g.fewest_cubes().power()).sum())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn example() -> Vec<String> {
+    fn example() -> &'static str {
         r#"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
 Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
 Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
 Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
     #[test]
     fn count_web() {
         let example_set = CubeSet::new_part1();
-        let games = parse(&example()).unwrap();
+        let games = parse_str(example()).unwrap();
         let possible_games = games
             .iter()
             .filter(|g| g.is_possible(&example_set))
             .collect::<Vec<_>>();
-        let sum = possible_games
+        let sum: i32 = possible_games
             .iter()
             .map(|g| g.id)
             .collect::<Vec<_>>()
@@ -169,10 +199,10 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"#
 
     #[test]
     fn fewest_web() {
-        let games = parse(&example()).unwrap();
+        let games = parse_str(example()).unwrap();
         let min_sets = games.iter().map(|g| g.fewest_cubes()).collect::<Vec<_>>();
         let powers = min_sets.iter().map(|s| s.power()).collect::<Vec<_>>();
-        let sum = powers.iter().sum();
+        let sum: i32 = powers.iter().sum();
         assert_eq!(2286, sum);
     }
 }
@@ -0,0 +1,182 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Problem found while parsing a puzzle input. Line and column numbers start at 1.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseError {
+    #[error("day {day:02}: input is empty")]
+    Empty { day: u8 },
+    #[error("day {day:02}, line {line}: missing {expected}")]
+    MissingLine {
+        day: u8,
+        line: usize,
+        expected: &'static str,
+    },
+    #[error("day {day:02}, line {line}, column {column}: expected {expected}, found {found:?}")]
+    Unexpected {
+        day: u8,
+        line: usize,
+        column: usize,
+        expected: &'static str,
+        found: String,
+    },
+    #[error("day {day:02}, line {line}, column {column}: invalid number {found:?}")]
+    InvalidNumber {
+        day: u8,
+        line: usize,
+        column: usize,
+        found: String,
+    },
+    #[error("day {day:02}, line {line}: could not read input: {message}")]
+    Io {
+        day: u8,
+        line: usize,
+        message: String,
+    },
+}
+
+pub type ParseResult<T> = std::result::Result<T, ParseError>;
+
+/// One line of puzzle input, used to build `ParseError`s pointing into it
+#[derive(Debug, Clone, Copy)]
+pub struct InputLine<'a> {
+    pub day: u8,
+    /// Index into the input, starting at 0
+    pub index: usize,
+    pub text: &'a str,
+}
+
+impl<'a> InputLine<'a> {
+    pub fn new(day: u8, index: usize, text: &'a str) -> InputLine<'a> {
+        InputLine { day, index, text }
+    }
+
+    /// Column of `token`, which has to be a slice of this line. Anything else points to
+    /// the start of the line.
+    pub fn column_of(&self, token: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let pos = token.as_ptr() as usize;
+        if pos < start || pos > start + self.text.len() {
+            return 1;
+        }
+        match self.text.get(..pos - start) {
+            Some(before) => before.chars().count() + 1,
+            None => 1,
+        }
+    }
+
+    /// Column just behind the end of the line, where a missing token would have been
+    pub fn end_column(&self) -> usize {
+        self.text.chars().count() + 1
+    }
+
+    pub fn number<T: FromStr>(&self, token: &str) -> ParseResult<T> {
+        token.parse::<T>().map_err(|_| ParseError::InvalidNumber {
+            day: self.day,
+            line: self.index + 1,
+            column: self.column_of(token),
+            found: token.to_string(),
+        })
+    }
+
+    pub fn unexpected(&self, token: &str, expected: &'static str) -> ParseError {
+        ParseError::Unexpected {
+            day: self.day,
+            line: self.index + 1,
+            column: self.column_of(token),
+            expected,
+            found: token.to_string(),
+        }
+    }
+
+    pub fn unexpected_at(&self, column: usize, found: char, expected: &'static str) -> ParseError {
+        ParseError::Unexpected {
+            day: self.day,
+            line: self.index + 1,
+            column,
+            expected,
+            found: found.to_string(),
+        }
+    }
+
+    pub fn missing(&self, expected: &'static str) -> ParseError {
+        ParseError::Unexpected {
+            day: self.day,
+            line: self.index + 1,
+            column: self.end_column(),
+            expected,
+            found: String::new(),
+        }
+    }
+
+    /// `token` or an error saying `expected` was missing at the end of the line
+    pub fn require<T>(&self, token: Option<T>, expected: &'static str) -> ParseResult<T> {
+        token.ok_or_else(|| self.missing(expected))
+    }
+
+    /// Fails at the first character not contained in `allowed`
+    pub fn check_chars(&self, allowed: &str, expected: &'static str) -> ParseResult<()> {
+        match self
+            .text
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !allowed.contains(*c))
+        {
+            Some((i, c)) => Err(self.unexpected_at(i + 1, c, expected)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns() {
+        let line = InputLine::new(7, 2, "32T3K 7x5");
+        let bid = line.text.split(' ').nth(1).unwrap();
+        assert_eq!(7, line.column_of(bid));
+        assert_eq!(10, line.end_column());
+        assert_eq!(1, line.column_of("elsewhere"));
+    }
+
+    #[test]
+    fn invalid_number() {
+        let line = InputLine::new(7, 2, "32T3K 7x5");
+        let bid = line.text.split(' ').nth(1).unwrap();
+        let err = line.number::<i32>(bid).unwrap_err();
+        assert_eq!(
+            ParseError::InvalidNumber {
+                day: 7,
+                line: 3,
+                column: 7,
+                found: "7x5".to_string()
+            },
+            err
+        );
+        assert_eq!(
+            "day 07, line 3, column 7: invalid number \"7x5\"",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn bad_characters() {
+        let line = InputLine::new(10, 0, "|-LX");
+        assert_eq!(
+            Err(ParseError::Unexpected {
+                day: 10,
+                line: 1,
+                column: 4,
+                expected: "pipe",
+                found: "X".to_string()
+            }),
+            line.check_chars("|-LJ7F.S", "pipe")
+        );
+        assert_eq!(Ok(()), line.check_chars("|-LJ7F.SX", "pipe"));
+    }
+}
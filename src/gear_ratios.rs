@@ -3,7 +3,12 @@
 
 // 2023-03
 
-use anyhow::{Context, Ok, Result};
+use anyhow::{bail, Context, Result};
+use std::io::BufRead;
+
+use crate::error::ParseResult;
+use crate::grid::{Grid, Position};
+use crate::solution::Solution;
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Location {
@@ -30,25 +35,38 @@ impl PartNumberSpan {
     }
 }
 
+impl Location {
+    pub fn position(&self) -> Position {
+        (self.line, self.column)
+    }
+}
+
+impl From<Position> for Location {
+    fn from(pos: Position) -> Location {
+        Location {
+            line: pos.0,
+            column: pos.1,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Schematic {
-    pub lines: Vec<String>,
+    pub grid: Grid<char>,
     pub symbols: Vec<Location>,
 }
 
 impl Schematic {
+    fn is_digit(&self, pos: Position) -> bool {
+        self.grid.get(pos).is_some_and(|c| c.is_ascii_digit())
+    }
+
     pub fn get_neighboring_digits(&self) -> Result<Vec<(Location, Location)>> {
         let mut result: Vec<(Location, Location)> = vec![];
         for loc in &self.symbols {
-            for line in loc.line - 1..loc.line + 2 {
-                for column in loc.column - 1..loc.column + 2 {
-                    if let Some(line_window) = self.lines.get(line) {
-                        if let Some(cha) = line_window.get(column..column + 1) {
-                            if cha.parse::<i32>().is_ok() {
-                                result.push((Location { line, column }, loc.clone()));
-                            }
-                        }
-                    }
+            for neighbor in self.grid.neighbors8(loc.position()) {
+                if self.is_digit(neighbor) {
+                    result.push((Location::from(neighbor), loc.clone()));
                 }
             }
         }
@@ -58,26 +76,13 @@ impl Schematic {
     pub fn unite(&self, candidates: &Vec<(Location, Location)>) -> Result<Vec<PartNumberSpan>> {
         let mut result: Vec<PartNumberSpan> = vec![];
 
-        let mut line = candidates
-            .first()
-            .context("Empty candidates list given")?
-            .0
-            .line;
-        let mut col_first = candidates
-            .first()
-            .context("Empty candidates list given")?
-            .0
-            .column;
-        let mut col_last = candidates
-            .first()
-            .context("Empty candidates list given")?
-            .0
-            .column;
-        let mut sym_last = candidates
-            .first()
-            .context("Empty candidates list given")?
-            .1
-            .clone();
+        let Some((first, first_symbol)) = candidates.first() else {
+            return Ok(result);
+        };
+        let mut line = first.line;
+        let mut col_first = first.column;
+        let mut col_last = first.column;
+        let mut sym_last = first_symbol.clone();
         for (candidate, neighbor_from) in candidates {
             if candidate.line == line && candidate.column == col_last {
                 continue;
@@ -117,61 +122,20 @@ impl Schematic {
         let mut result: Vec<PartNumberSpan> = vec![];
 
         for current in spans {
+            if !self.is_digit((current.line, current.col_first)) {
+                bail!("Span {:?} does not start on a digit", current);
+            }
+
             // First grow to the front
             let mut front = current.col_first;
-            let mut continue_growing = true;
-            while continue_growing {
-                if self
-                    .lines
-                    .get(current.line)
-                    .expect("inconsistent spans generated")
-                    .get(front..front + 1)
-                    .context("Out of bounds during front growing")?
-                    .parse::<usize>()
-                    .is_ok()
-                {
-                    match front.checked_sub(1) {
-                        Some(x) => {
-                            front = x;
-                        }
-                        None => {
-                            break;
-                        }
-                    }
-                } else {
-                    front += 1;
-                    continue_growing = false;
-                }
+            while front > 0 && self.is_digit((current.line, front - 1)) {
+                front -= 1;
             }
 
             // then grow to the back
             let mut back = current.col_last;
-            continue_growing = true;
-            while continue_growing {
-                if self
-                    .lines
-                    .get(current.line)
-                    .expect("inconsistent spans generated")
-                    .get(back..back + 1)
-                    .context("Out of bounds during back growing")?
-                    .parse::<usize>()
-                    .is_ok()
-                {
-                    back += 1;
-                    if back
-                        >= self
-                            .lines
-                            .get(current.line)
-                            .expect("inconsistent spans generated")
-                            .len()
-                    {
-                        back -= 1;
-                        break;
-                    }
-                } else {
-                    back -= 1;
-                    continue_growing = false;
-                }
+            while self.is_digit((current.line, back + 1)) {
+                back += 1;
             }
 
             result.push(PartNumberSpan::new(
@@ -184,74 +148,113 @@ impl Schematic {
         Ok(result)
     }
 
-    pub fn as_number(&self, span: &PartNumberSpan) -> i32 {
-        self.lines
-            .get(span.line)
-            .expect("inconsistent span given")
-            .get(span.col_first..span.col_last + 1)
-            .expect("inconsistent span given")
-            .parse::<i32>()
-            .expect("invalid span given")
+    pub fn as_number(&self, span: &PartNumberSpan) -> Result<i32> {
+        let digits = self
+            .grid
+            .row(span.line)
+            .and_then(|row| row.get(span.col_first..span.col_last + 1))
+            .with_context(|| format!("Span {:?} is outside of the schematic", span))?
+            .iter()
+            .collect::<String>();
+        digits.parse::<i32>().with_context(|| {
+            format!(
+                "Part number {digits} in row {}, column {} is too large",
+                span.line + 1,
+                span.col_first + 1
+            )
+        })
     }
 
     pub fn get_gears(&self, part_numbers: &[PartNumberSpan]) -> Result<Vec<Location>> {
-        let gear_candidates: Vec<Location> = self
+        let gear_candidates = self
             .symbols
             .iter()
-            .cloned()
-            .filter(|loc| {
-                self.lines
-                    .get(loc.line)
-                    .expect("inconsistent symbols")
-                    .get(loc.column..loc.column + 1)
-                    .expect("inconsistent symbols")
-                    == "*"
-            })
-            .collect::<Vec<Location>>()
-            .to_vec();
-        //.iter().cloned().collect::<Vec<_>>();
+            .filter(|loc| self.grid.get(loc.position()) == Some(&'*'))
+            .collect::<Vec<_>>();
 
         let gears = gear_candidates
-            .iter()
-            .cloned()
+            .into_iter()
             .filter(|cand| {
                 part_numbers
                     .iter()
-                    .filter(|span| &span.symbol == cand)
-                    .collect::<Vec<_>>()
-                    .len()
+                    .filter(|span| &span.symbol == *cand)
+                    .count()
                     == 2
             })
+            .cloned()
             .collect::<Vec<_>>();
 
         Ok(gears)
     }
 }
 
-pub fn parse(lines: &[String]) -> Result<Schematic> {
-    let mut symbs: Vec<Location> = vec![];
-    for (line_nb, _) in lines.iter().enumerate() {
-        for (idx, cha) in lines[line_nb].char_indices() {
-            if !(cha.is_ascii_digit() || cha == '.') {
-                symbs.push(Location {
-                    line: line_nb,
-                    column: idx,
-                });
-            }
-        }
+pub fn parse<R: BufRead>(input: R) -> ParseResult<Schematic> {
+    let grid = Grid::parse(Day03::DAY, input, "character", Some)?;
+    let symbols = grid
+        .iter()
+        .filter(|(_, cha)| !(cha.is_ascii_digit() || **cha == '.'))
+        .map(|(pos, _)| Location::from(pos))
+        .collect();
+
+    Ok(Schematic { grid, symbols })
+}
+
+pub fn parse_str(text: &str) -> ParseResult<Schematic> {
+    parse(text.as_bytes())
+}
+
+pub struct Day03;
+
+impl Day03 {
+    fn part_numbers(schematic: &Schematic) -> Result<Vec<PartNumberSpan>> {
+        schematic.grow(&schematic.unite(&schematic.get_neighboring_digits()?)?)
     }
+}
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    const NAME: &'static str = "gear_ratios";
+
+    type Input = Schematic;
+    type Answer1 = i32;
+    type Answer2 = i32;
 
-    Ok(Schematic {
-        lines: lines.to_owned(),
-        symbols: symbs.clone(),
-    })
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        let part_numbers = Day03::part_numbers(input)?;
+        part_numbers.iter().try_fold(0_i32, |sum, span| {
+            sum.checked_add(input.as_number(span)?)
+                .context("Sum of the part numbers is too large")
+        })
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        let part_numbers = Day03::part_numbers(input)?;
+        let gears = input.get_gears(&part_numbers)?;
+        let geared_part_numbers = part_numbers
+            .iter()
+            .filter(|pn| gears.contains(&pn.symbol))
+            .collect::<Vec<_>>();
+        let mult1 = geared_part_numbers.iter().step_by(2);
+        let mult2 = geared_part_numbers.iter().skip(1).step_by(2);
+        mult1.zip(mult2).try_fold(0_i32, |sum, (a, b)| {
+            input
+                .as_number(a)?
+                .checked_mul(input.as_number(b)?)
+                .and_then(|ratio| sum.checked_add(ratio))
+                .context("Sum of the gear ratios is too large")
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn example() -> Vec<String> {
+    fn example() -> &'static str {
         r#"467..114..
 ...*......
 ..35..633.
@@ -262,9 +265,6 @@ mod tests {
 ......755.
 ...$.*....
 .664.598.."#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
     fn example_symbols() -> Vec<Location> {
@@ -282,9 +282,9 @@ mod tests {
 
     #[test]
     fn sum_web() {
-        let schematic = parse(&example()).unwrap();
+        let schematic = parse_str(example()).unwrap();
         assert_eq!(example_symbols(), schematic.symbols);
-        let direct_neighbors: Vec<(Location, Location)> = (|| {
+        let direct_neighbors: Vec<(Location, Location)> = {
             let mut result = vec![
                 (
                     Location { line: 0, column: 2 },
@@ -337,17 +337,14 @@ mod tests {
             ];
             result.sort();
             result
-        })();
-        assert_eq!(
-            direct_neighbors,
-            (|| {
-                let mut result = schematic.get_neighboring_digits().unwrap();
-                result.sort();
-                result
-            })()
-        );
+        };
+        assert_eq!(direct_neighbors, {
+            let mut result = schematic.get_neighboring_digits().unwrap();
+            result.sort();
+            result
+        });
 
-        let united_neighbors: Vec<PartNumberSpan> = (|| {
+        let united_neighbors: Vec<PartNumberSpan> = {
             let mut result = vec![
                 PartNumberSpan::new(0, 2, 2, Location { line: 1, column: 3 }),
                 PartNumberSpan::new(2, 2, 3, Location { line: 1, column: 3 }),
@@ -360,17 +357,14 @@ mod tests {
             ];
             result.sort();
             result
-        })();
-        assert_eq!(
-            united_neighbors,
-            (|| {
-                let mut result = schematic.unite(&direct_neighbors).unwrap();
-                result.sort();
-                result
-            })()
-        );
+        };
+        assert_eq!(united_neighbors, {
+            let mut result = schematic.unite(&direct_neighbors).unwrap();
+            result.sort();
+            result
+        });
 
-        let grown: Vec<PartNumberSpan> = (|| {
+        let grown: Vec<PartNumberSpan> = {
             let mut result = vec![
                 PartNumberSpan::new(0, 0, 2, Location { line: 1, column: 3 }),
                 PartNumberSpan::new(2, 2, 3, Location { line: 1, column: 3 }),
@@ -383,23 +377,18 @@ mod tests {
             ];
             result.sort();
             result
-        })();
-        assert_eq!(
-            grown,
-            (|| {
-                let mut result = schematic.grow(&united_neighbors).unwrap();
-                result.sort();
-                result
-            })()
-        );
+        };
+        assert_eq!(grown, {
+            let mut result = schematic.grow(&united_neighbors).unwrap();
+            result.sort();
+            result
+        });
         assert_eq!(
             4361,
             grown
                 .iter()
-                .map(|span| schematic.as_number(span))
-                .collect::<Vec<_>>()
-                .iter()
-                .sum()
+                .map(|span| schematic.as_number(span).unwrap())
+                .sum::<i32>()
         );
 
         let gears = vec![
@@ -408,4 +397,25 @@ mod tests {
         ];
         assert_eq!(gears, schematic.get_gears(&grown).unwrap());
     }
+
+    #[test]
+    fn large_numbers() {
+        let schematic = parse_str("99999999999*\n").unwrap();
+        let err = Day03::part1(&schematic).unwrap_err();
+        assert_eq!(
+            "Part number 99999999999 in row 1, column 1 is too large",
+            err.to_string()
+        );
+
+        let schematic = parse_str("99999*99999").unwrap();
+        assert!(Day03::part1(&schematic).is_ok());
+        assert!(Day03::part2(&schematic).is_err());
+    }
+
+    #[test]
+    fn no_symbols() {
+        let schematic = parse_str("...\n.1.\n").unwrap();
+        assert_eq!(0, Day03::part1(&schematic).unwrap());
+        assert_eq!(0, Day03::part2(&schematic).unwrap());
+    }
 }
@@ -0,0 +1,323 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::{self, Display};
+use std::io::BufRead;
+
+use crate::error::ParseResult;
+use crate::input::LineReader;
+
+/// (row, column), starting at the top left
+pub type Position = (usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn all() -> [Direction; 4] {
+        [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
+/// Dense, rectangular grid stored row by row
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Builds a grid from its rows, `None` if they are not all of the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Grid<T>> {
+        let width = rows.first().map(|r| r.len()).unwrap_or(0);
+        if rows.iter().any(|r| r.len() != width) {
+            return None;
+        }
+        Some(Grid {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parses one cell per character, skipping empty lines. `cell` returns `None` for
+    /// characters which are not allowed, these are reported as expecting `expected`.
+    pub fn parse<R, F>(
+        day: u8,
+        input: R,
+        expected: &'static str,
+        mut cell: F,
+    ) -> ParseResult<Grid<T>>
+    where
+        R: BufRead,
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut lines = LineReader::new(day, input);
+        let mut rows = Vec::new();
+        while let Some(line) = lines.next_non_empty()? {
+            let mut row = Vec::new();
+            for (column, cha) in line.text.chars().enumerate() {
+                match cell(cha) {
+                    Some(value) => row.push(value),
+                    None => return Err(line.unexpected_at(column + 1, cha, expected)),
+                }
+            }
+            if rows
+                .first()
+                .is_some_and(|first: &Vec<T>| first.len() != row.len())
+            {
+                return Err(line.unexpected(line.text, "rows of equal width"));
+            }
+            rows.push(row);
+        }
+        Ok(Grid::from_rows(rows).expect("rows were checked to be of equal width"))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        pos.0 < self.height && pos.1 < self.width
+    }
+
+    pub fn get(&self, pos: Position) -> Option<&T> {
+        if self.contains(pos) {
+            self.cells.get(pos.0 * self.width + pos.1)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Position) -> Option<&mut T> {
+        if self.contains(pos) {
+            self.cells.get_mut(pos.0 * self.width + pos.1)
+        } else {
+            None
+        }
+    }
+
+    /// All positions, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |column| (row, column)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        if row < self.height {
+            Some(&self.cells[row * self.width..(row + 1) * self.width])
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|row| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    pub fn column(&self, column: usize) -> impl Iterator<Item = &T> {
+        let width = self.width;
+        let count = if column < width { self.height } else { 0 };
+        self.cells
+            .iter()
+            .skip(column)
+            .step_by(width.max(1))
+            .take(count)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|column| self.column(column))
+    }
+
+    /// The neighbouring position in `direction`, if it is inside the grid
+    pub fn step(&self, pos: Position, direction: Direction) -> Option<Position> {
+        let next = match direction {
+            Direction::Up => (pos.0.checked_sub(1)?, pos.1),
+            Direction::Down => (pos.0 + 1, pos.1),
+            Direction::Left => (pos.0, pos.1.checked_sub(1)?),
+            Direction::Right => (pos.0, pos.1 + 1),
+        };
+        if self.contains(next) {
+            Some(next)
+        } else {
+            None
+        }
+    }
+
+    /// Horizontal and vertical neighbours inside the grid
+    pub fn neighbors4(&self, pos: Position) -> impl Iterator<Item = Position> + '_ {
+        Direction::all()
+            .into_iter()
+            .filter_map(move |d| self.step(pos, d))
+    }
+
+    /// Horizontal, vertical and diagonal neighbours inside the grid, row by row
+    pub fn neighbors8(&self, pos: Position) -> impl Iterator<Item = Position> + '_ {
+        (-1..=1_isize)
+            .flat_map(|dr| (-1..=1_isize).map(move |dc| (dr, dc)))
+            .filter(|&offset| offset != (0, 0))
+            .filter_map(move |(dr, dc)| {
+                let next = (pos.0.checked_add_signed(dr)?, pos.1.checked_add_signed(dc)?);
+                if self.contains(next) {
+                    Some(next)
+                } else {
+                    None
+                }
+            })
+    }
+
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let cells = (0..self.width)
+            .flat_map(|column| self.column(column).cloned())
+            .collect();
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells,
+        }
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ParseError;
+
+    fn example() -> &'static str {
+        r#"
+abc
+def"#
+    }
+
+    fn chars(text: &str) -> Grid<char> {
+        Grid::parse(0, text.as_bytes(), "letter", Some).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let grid = chars(example());
+        assert_eq!(3, grid.width());
+        assert_eq!(2, grid.height());
+        assert_eq!(Some(&'f'), grid.get((1, 2)));
+        assert_eq!(None, grid.get((2, 0)));
+        assert_eq!(None, grid.get((0, 3)));
+        assert_eq!("abc\ndef", grid.to_string());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            Grid::parse(0, "abc\nde".as_bytes(), "letter", Some).unwrap_err(),
+            ParseError::Unexpected { line: 2, .. }
+        ));
+        assert_eq!(
+            ParseError::Unexpected {
+                day: 11,
+                line: 2,
+                column: 2,
+                expected: "galaxy",
+                found: "x".to_string()
+            },
+            Grid::parse(11, "#.\n.x".as_bytes(), "galaxy", |c| match c {
+                '#' => Some(true),
+                '.' => Some(false),
+                _ => None,
+            })
+            .unwrap_err()
+        );
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = chars(example());
+        assert_eq!(Some(&['d', 'e', 'f'][..]), grid.row(1));
+        assert_eq!(
+            vec!["ad", "be", "cf"],
+            grid.columns()
+                .map(|c| c.collect::<String>())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(0, grid.column(3).count());
+        assert_eq!("ad\nbe\ncf", grid.transpose().to_string());
+        assert_eq!(grid, grid.transpose().transpose());
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = chars(example());
+        assert_eq!(
+            vec![(1, 0), (0, 1)],
+            grid.neighbors4((0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(0, 0), (0, 2), (1, 0), (1, 1), (1, 2)],
+            grid.neighbors8((0, 1)).collect::<Vec<_>>()
+        );
+        assert_eq!(None, grid.step((1, 2), Direction::Right));
+        assert_eq!(Some((0, 2)), grid.step((1, 2), Direction::Up));
+    }
+}
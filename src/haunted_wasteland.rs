@@ -3,11 +3,16 @@
 
 // 2023-08
 
-use anyhow::{Context, Ok, Result};
+use anyhow::{bail, Context, Result};
 use num::Integer;
 use rayon::prelude::*;
 use regex::Regex;
 use std::collections::HashMap;
+use std::io::BufRead;
+
+use crate::error::{ParseError, ParseResult};
+use crate::input::LineReader;
+use crate::solution::Solution;
 
 #[derive(Debug, Clone)]
 pub struct Map {
@@ -38,61 +43,98 @@ impl Map {
         Ok(moves)
     }
 
+    /// The node reached from `location` when following `direction`
+    fn step<'a>(&'a self, location: &str, direction: char) -> Result<&'a str> {
+        let node = self
+            .network
+            .get(location)
+            .with_context(|| format!("location {location} not found"))?;
+        if direction == 'L' {
+            Ok(&node.0)
+        } else {
+            Ok(&node.1)
+        }
+    }
+
     pub fn moves_ghost_bruteforce(&self) -> Result<u32> {
+        let moves = self
+            .moves_ghost_bruteforce_within(u32::MAX.into())?
+            .context("Ghosts never stand on Z nodes at the same time")?;
+        Ok(moves as u32)
+    }
+
+    /// Moves all ghosts in lockstep, giving up after `max_moves` moves
+    pub fn moves_ghost_bruteforce_within(&self, max_moves: u64) -> Result<Option<u64>> {
         let mut moves = 0;
-        let mut location: Vec<_> = self.network.keys().filter(|k| k.ends_with('A')).collect();
+        let mut location: Vec<&str> = self
+            .network
+            .keys()
+            .filter(|k| k.ends_with('A'))
+            .map(|k| k.as_str())
+            .collect();
         for current_move in self.directions.chars().cycle() {
-            let working_location = location.clone();
-            location.clear();
-            location.par_extend(working_location.par_iter().map(|current_location| {
-                let node = self
-                    .network
-                    .get(current_location.as_str())
-                    .expect("location {location} not found");
-                if current_move == 'L' {
-                    &node.0
-                } else {
-                    &node.1
-                }
-            }));
+            if moves == max_moves {
+                return Ok(None);
+            }
+            location = location
+                .par_iter()
+                .map(|current_location| self.step(current_location, current_move))
+                .collect::<Result<_>>()?;
             moves += 1;
 
-            if location
-                .par_iter()
-                .filter(|l| !l.ends_with('Z'))
-                .collect::<Vec<_>>()
-                .is_empty()
-            {
+            if location.par_iter().all(|l| l.ends_with('Z')) {
                 break;
             }
         }
-        Ok(moves)
+        Ok(Some(moves))
     }
 
-    pub fn moves_ghost(&self) -> Result<u64> {
+    /// Moves until the ghost starting at `start` first stands on a Z node.
+    ///
+    /// Only answers if the ghost is on a Z node exactly at every multiple of that
+    /// number of moves, as the LCM over all ghosts is wrong otherwise. This is
+    /// checked by walking until a (node, direction index) state repeats.
+    fn ghost_period(&self, start: &str) -> Result<u64> {
+        if self.directions.is_empty() {
+            bail!("No directions given");
+        }
+        let mut seen = HashMap::new();
+        let mut on_z = vec![false];
+        let mut location = start;
         let mut moves = 0;
+        let cycle_start = loop {
+            let index = moves % self.directions.len();
+            if let Some(&first) = seen.get(&(location, index)) {
+                break first;
+            }
+            seen.insert((location, index), moves);
+            let direction = self.directions.as_bytes()[index] as char;
+            location = self.step(location, direction)?;
+            moves += 1;
+            on_z.push(location.ends_with('Z'));
+        };
+        let cycle_length = moves - cycle_start;
+
+        let Some(period) = on_z.iter().position(|z| *z) else {
+            bail!("Ghost starting at {start} never reaches a Z node");
+        };
+        if cycle_length % period != 0
+            || on_z
+                .iter()
+                .enumerate()
+                .skip(1)
+                .any(|(moves, z)| *z != (moves % period == 0))
+        {
+            bail!("Ghost starting at {start} does not reach Z nodes every {period} moves");
+        }
+        Ok(period as u64)
+    }
+
+    pub fn moves_ghost(&self) -> Result<u64> {
         let mut all_moves: Vec<u64> = Vec::new();
         let location: Vec<_> = self.network.keys().filter(|k| k.ends_with('A')).collect();
         for current_location in location {
-            let mut tmp_location = current_location;
-            for current_move in self.directions.chars().cycle() {
-                let node = self
-                    .network
-                    .get(tmp_location)
-                    .expect("location {location} not found");
-                if current_move == 'L' {
-                    tmp_location = &node.0;
-                } else {
-                    tmp_location = &node.1;
-                }
-                moves += 1;
-
-                if tmp_location.ends_with('Z') {
-                    break;
-                }
-            }
-            all_moves.push(moves);
-            moves = 0;
+            all_moves.push(self.ghost_period(current_location)?);
         }
         let moves = all_moves
             .into_iter()
@@ -102,15 +144,22 @@ impl Map {
     }
 }
 
-pub fn parse(lines: &[String]) -> Result<Map> {
-    let directions = lines.get(0).context("no directions given")?;
-    let re = Regex::new(r"(?<from>\w{3}) = \((?<left>\w{3}), (?<right>\w{3})\)")
-        .context("Could not compile regex")?;
+pub fn parse<R: BufRead>(input: R) -> ParseResult<Map> {
+    let mut lines = LineReader::new(Day08::DAY, input);
+    let directions = match lines.next_non_empty()? {
+        Some(line) => {
+            line.check_chars("LR", "direction L or R")?;
+            line.text.to_string()
+        }
+        None => return Err(ParseError::Empty { day: Day08::DAY }),
+    };
+    let re = Regex::new(r"^(?<from>\w{3}) = \((?<left>\w{3}), (?<right>\w{3})\)$")
+        .expect("Could not compile regex");
     let mut network = HashMap::new();
 
-    for line in lines.iter().filter(|s| !s.is_empty()).skip(1) {
-        let Some(captures) = re.captures(line) else {
-            panic!("Line {line} did not match regex");
+    while let Some(line) = lines.next_non_empty()? {
+        let Some(captures) = re.captures(line.text) else {
+            return Err(line.unexpected(line.text, "node like \"AAA = (BBB, CCC)\""));
         };
 
         network.insert(
@@ -119,16 +168,46 @@ pub fn parse(lines: &[String]) -> Result<Map> {
         );
     }
     Ok(Map {
-        directions: directions.to_string(),
+        directions,
         network,
     })
 }
 
+pub fn parse_str(text: &str) -> ParseResult<Map> {
+    parse(text.as_bytes())
+}
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+    const NAME: &'static str = "haunted_wasteland";
+
+    type Input = Map;
+    type Answer1 = u32;
+    type Answer2 = u64;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<u32> {
+        input.moves()
+    }
+
+    fn part2(input: &Self::Input) -> Result<u64> {
+        input.moves_ghost()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ParseError;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
 
-    fn example() -> Vec<String> {
+    fn example() -> &'static str {
         r#"RL
 
 AAA = (BBB, CCC)
@@ -138,23 +217,17 @@ DDD = (DDD, DDD)
 EEE = (EEE, EEE)
 GGG = (GGG, GGG)
 ZZZ = (ZZZ, ZZZ)"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
-    fn example2() -> Vec<String> {
+    fn example2() -> &'static str {
         r#"LLR
 
 AAA = (BBB, BBB)
 BBB = (AAA, ZZZ)
 ZZZ = (ZZZ, ZZZ)"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
-    fn example_part2() -> Vec<String> {
+    fn example_part2() -> &'static str {
         r#"LR
 
 11A = (11B, XXX)
@@ -165,26 +238,142 @@ ZZZ = (ZZZ, ZZZ)"#
 22C = (22Z, 22Z)
 22Z = (22B, 22B)
 XXX = (XXX, XXX)"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
     #[test]
     fn result_web() {
-        let map = parse(&example()).unwrap();
+        let map = parse_str(example()).unwrap();
         assert_eq!(2, map.moves().unwrap());
     }
 
     #[test]
     fn result_web2() {
-        let map = parse(&example2()).unwrap();
+        let map = parse_str(example2()).unwrap();
         assert_eq!(6, map.moves().unwrap());
     }
 
     #[test]
     fn result_part2() {
-        let map = parse(&example_part2()).unwrap();
+        let map = parse_str(example_part2()).unwrap();
         assert_eq!(6, map.moves_ghost().unwrap());
     }
+
+    #[test]
+    fn ghosts_out_of_step() {
+        // 22A stands on 22Z after 1, 4, 7, ... moves, so the LCM of the
+        // first arrivals would claim 2
+        let map = parse_str(
+            r#"L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)"#,
+        )
+        .unwrap();
+        assert!(map.moves_ghost().is_err());
+        assert_eq!(4, map.moves_ghost_bruteforce().unwrap());
+    }
+
+    /// Small random networks; the ghosts need not ever line up
+    fn any_map() -> impl Strategy<Value = Map> {
+        let node = (
+            prop::sample::select(vec!['A', 'X', 'Z']),
+            0..6_usize,
+            0..6_usize,
+        );
+        ("[LR]{1,4}", vec(node, 1..6)).prop_map(|(directions, nodes)| {
+            let names = nodes
+                .iter()
+                .enumerate()
+                .map(|(i, (kind, _, _))| format!("{i}{}", if i == 0 { 'A' } else { *kind }))
+                .collect::<Vec<_>>();
+            let network = nodes
+                .iter()
+                .enumerate()
+                .map(|(i, (_, left, right))| {
+                    let left = names[left % names.len()].clone();
+                    let right = names[right % names.len()].clone();
+                    (names[i].clone(), (left, right))
+                })
+                .collect();
+            Map {
+                directions,
+                network,
+            }
+        })
+    }
+
+    /// Every ghost walks a loop of the given length that ends on its Z node, as in
+    /// the puzzle input
+    fn looping_map() -> impl Strategy<Value = (Map, Vec<u64>)> {
+        ("[LR]{1,4}", vec(1..6_u64, 1..4)).prop_map(|(directions, lengths)| {
+            let mut network = HashMap::new();
+            for (ghost, length) in lengths.iter().enumerate() {
+                let name = |i: u64| {
+                    if i == *length {
+                        format!("{ghost}Z")
+                    } else {
+                        format!("{ghost}{i}")
+                    }
+                };
+                network.insert(format!("{ghost}A"), (name(1), name(1)));
+                for i in 1..=*length {
+                    let next = name(i % length + 1);
+                    network.insert(name(i), (next.clone(), next));
+                }
+            }
+            let map = Map {
+                directions,
+                network,
+            };
+            (map, lengths)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn lcm_agrees_with_bruteforce(map in any_map()) {
+            if let Ok(moves) = map.moves_ghost() {
+                prop_assume!(moves <= 100_000);
+                prop_assert_eq!(Some(moves), map.moves_ghost_bruteforce_within(moves).unwrap());
+            }
+        }
+
+        #[test]
+        fn lcm_of_looping_ghosts((map, lengths) in looping_map()) {
+            let expected = lengths.into_iter().reduce(|a, b| a.lcm(&b)).unwrap();
+            prop_assert_eq!(expected, map.moves_ghost().unwrap());
+            prop_assert_eq!(Some(expected), map.moves_ghost_bruteforce_within(expected).unwrap());
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let lines = example().replace("BBB = (DDD, EEE)", "BBB = (DDD EEE)");
+        assert_eq!(
+            ParseError::Unexpected {
+                day: 8,
+                line: 4,
+                column: 1,
+                expected: "node like \"AAA = (BBB, CCC)\"",
+                found: "BBB = (DDD EEE)".to_string()
+            },
+            parse_str(&lines).unwrap_err()
+        );
+
+        let lines = example().replacen("RL", "RLX", 1);
+        assert!(matches!(
+            parse_str(&lines).unwrap_err(),
+            ParseError::Unexpected {
+                line: 1,
+                column: 3,
+                ..
+            }
+        ));
+        assert_eq!(ParseError::Empty { day: 8 }, parse_str("\n\n").unwrap_err());
+    }
 }
@@ -3,15 +3,19 @@
 
 // 2023-12
 
-use anyhow::{Ok, Result};
-use regex::Regex;
+use anyhow::Result;
+use rayon::prelude::*;
+use std::io::BufRead;
+
+use crate::error::{InputLine, ParseResult};
+use crate::input::LineReader;
+use crate::solution::Solution;
 
 #[derive(Debug, Clone)]
 pub struct Spring {
     pub original: String,
     step: usize,
-    check: Regex,
-    nb_check: usize,
+    groups: Vec<usize>,
     placeholders: Vec<usize>,
 }
 
@@ -37,7 +41,7 @@ impl Iterator for Spring {
             let result = result.replace('0', ".");
             let result = result.replace('1', "#");
 
-                Some(result)
+            Some(result)
         } else {
             None
         }
@@ -45,89 +49,171 @@ impl Iterator for Spring {
 }
 
 impl Spring {
-    pub fn new(line: String, checkstring: String, unfold: bool) -> Spring {
-        let mut checkstring = checkstring;
-        if unfold {
-            checkstring = format!(
-                "{},{},{},{},{}",
-                checkstring, checkstring, checkstring, checkstring, checkstring
-            );
-        }
-
-        let mut line = line;
-        if unfold {
-            line = format!("{}?{}?{}?{}?{}", line, line, line, line, line);
-        }
+    /// Fails like `parse` would on the line `"{line} {checkstring}"`
+    pub fn new(line: String, checkstring: String, unfold: bool) -> ParseResult<Spring> {
+        let record = format!("{line} {checkstring}");
+        let (springs, groups) = parse_record(&InputLine::new(Day12::DAY, 0, &record))?;
+        Ok(Spring::from_record(springs, groups, unfold))
+    }
 
-        let mut regex_str = r"^\.*".to_owned();
-        let mut nb_check = 0;
-        for number in checkstring.split(',') {
-            regex_str.push_str(&format!(r"#{{{}}}\.+", number.parse::<i32>().unwrap()));
-            nb_check += number.parse::<usize>().unwrap();
-        }
-        regex_str.pop();
-        regex_str.push_str("*$");
-        let check = Regex::new(&regex_str).unwrap();
+    fn from_record(line: &str, groups: Vec<usize>, unfold: bool) -> Spring {
+        let (line, groups) = if unfold {
+            ([line; 5].join("?"), groups.repeat(5))
+        } else {
+            (line.to_string(), groups)
+        };
 
         Spring {
             step: 0,
-            check,
             placeholders: line
                 .char_indices()
                 .filter_map(|(i, c)| if c == '?' { Some(i) } else { None })
                 .collect::<Vec<_>>(),
             original: line,
-            nb_check,
+            groups,
         }
     }
 
     pub fn get_arrangements(&mut self) -> u64 {
         let mut result = 0;
-        let check = self.check.clone();
-        let nb_check = self.nb_check;
+        let groups = self.groups.clone();
         for i in self {
-            let nb_damaged = i.chars().filter(|c| *c == '#').collect::<Vec<_>>().len();
-                if nb_damaged == nb_check && check.is_match(&i) {
-                    result += 1;
-                }
+            if i.split('.')
+                .filter(|damaged| !damaged.is_empty())
+                .map(|damaged| damaged.len())
+                .eq(groups.iter().copied())
+            {
+                result += 1;
+            }
         }
-        println!("{}", result);
         result
     }
+
+    /// Counts the arrangements without trying each of them, so unfolded rows are feasible
+    pub fn count_arrangements(&self) -> u64 {
+        let springs = self.original.as_bytes();
+        let groups = &self.groups;
+        // ways[i][j]: arrangements of springs[i..] that hold exactly groups[j..]
+        let mut ways = vec![vec![0_u64; groups.len() + 1]; springs.len() + 1];
+        ways[springs.len()][groups.len()] = 1;
+        for i in (0..springs.len()).rev() {
+            for j in 0..=groups.len() {
+                let mut count = 0;
+                if springs[i] != b'#' {
+                    count += ways[i + 1][j];
+                }
+                if j < groups.len() && springs[i] != b'.' {
+                    let end = i + groups[j];
+                    if end <= springs.len()
+                        && !springs[i..end].contains(&b'.')
+                        && springs.get(end) != Some(&b'#')
+                    {
+                        count += ways[(end + 1).min(springs.len())][j + 1];
+                    }
+                }
+                ways[i][j] = count;
+            }
+        }
+        ways[0][0]
+    }
+}
+
+/// Spring conditions and group sizes of a row
+fn parse_record<'a>(line: &InputLine<'a>) -> ParseResult<(&'a str, Vec<usize>)> {
+    let (springs, groups) = line.require(line.text.split_once(' '), "group sizes")?;
+    if let Some((i, c)) = springs
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !".#?".contains(*c))
+    {
+        return Err(line.unexpected_at(i + 1, c, "spring condition"));
+    }
+    let groups = groups
+        .split(',')
+        .map(|group| {
+            let size = line.number::<usize>(group)?;
+            if size == 0 {
+                return Err(line.unexpected(group, "group size above 0"));
+            }
+            Ok(size)
+        })
+        .collect::<ParseResult<Vec<_>>>()?;
+    Ok((springs, groups))
 }
 
-pub fn parse(lines: &[String], unfold: bool) -> Result<Vec<Spring>> {
+/// Spring conditions and group sizes of every row
+fn parse_records<R: BufRead>(input: R) -> ParseResult<Vec<(String, Vec<usize>)>> {
     let mut result = Vec::new();
-    for line in lines {
-        let parts = line.split(' ').collect::<Vec<_>>();
-        result.push(Spring::new(
-            parts[0].to_string(),
-            parts[1].to_string(),
-            unfold,
-        ));
+    let mut lines = LineReader::new(Day12::DAY, input);
+    while let Some(line) = lines.next_line()? {
+        let (springs, groups) = parse_record(&line)?;
+        result.push((springs.to_string(), groups));
     }
     Ok(result)
 }
 
+pub fn parse<R: BufRead>(input: R, unfold: bool) -> ParseResult<Vec<Spring>> {
+    Ok(parse_records(input)?
+        .into_iter()
+        .map(|(springs, groups)| Spring::from_record(&springs, groups, unfold))
+        .collect())
+}
+
+pub fn parse_str(text: &str, unfold: bool) -> ParseResult<Vec<Spring>> {
+    parse(text.as_bytes(), unfold)
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    const NAME: &'static str = "hot_springs";
+
+    type Input = (Vec<Spring>, Vec<Spring>);
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {
+        let records = parse_records(input)?;
+        let springs = records
+            .iter()
+            .map(|(springs, groups)| Spring::from_record(springs, groups.clone(), false))
+            .collect();
+        let unfolded = records
+            .into_iter()
+            .map(|(springs, groups)| Spring::from_record(&springs, groups, true))
+            .collect();
+        Ok((springs, unfolded))
+    }
+
+    fn part1(input: &Self::Input) -> Result<u64> {
+        Ok(input.0.par_iter().map(|s| s.count_arrangements()).sum())
+    }
+
+    fn part2(input: &Self::Input) -> Result<u64> {
+        Ok(input.1.par_iter().map(|s| s.count_arrangements()).sum())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ParseError;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
 
-    fn example() -> Vec<String> {
+    fn example() -> &'static str {
         r#"???.### 1,1,3
 .??..??...?##. 1,1,3
 ?#?#?#?#?#?#?#? 1,3,1,6
 ????.#...#... 4,1,1
 ????.######..#####. 1,6,5
 ?###???????? 3,2,1"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
     #[test]
     fn result_web() {
-        let mut springs = parse(&example(), false).unwrap();
+        let mut springs = parse_str(example(), false).unwrap();
         assert_eq!(
             vec![1, 4, 1, 1, 4, 10],
             springs
@@ -137,47 +223,91 @@ mod tests {
         );
     }
 
+    #[test]
+    fn group_longer_than_row() {
+        let mut springs = parse_str("?? 3", false).unwrap();
+        assert_eq!(0, springs[0].count_arrangements());
+        assert_eq!(0, springs[0].get_arrangements());
+    }
+
     #[test]
     fn result_web_one() {
-        let mut springs = parse(&vec!["???.### 1,1,3".to_owned()], false).unwrap();
+        let mut springs = parse_str("???.### 1,1,3", false).unwrap();
         assert_eq!(1_u64, springs[0].get_arrangements());
     }
 
     #[test]
     fn result_inputs() {
-        let mut springs = parse(&vec!["?????????? 1,1,4".to_owned()], false).unwrap(); // 29
+        let mut springs = parse_str("?????????? 1,1,4", false).unwrap(); // 29
         assert_eq!(10_u64, springs[0].get_arrangements());
 
-        let mut springs = parse(&vec!["????.?#.?????????? 4,1,1,1,1,1".to_owned()], false).unwrap(); // 238
+        let mut springs = parse_str("????.?#.?????????? 4,1,1,1,1,1", false).unwrap(); // 238
         assert_eq!(35_u64, springs[0].get_arrangements());
 
-        let mut springs = parse(&vec!["..????.??..?? 1,2,1,1".to_owned()], false).unwrap(); // 991
+        let mut springs = parse_str("..????.??..?? 1,2,1,1", false).unwrap(); // 991
         assert_eq!(4_u64, springs[0].get_arrangements());
 
-        let mut springs = parse(&vec!["?#????##??##???.#??# 4,2,6,1,1".to_owned()], false).unwrap(); // 997
+        let mut springs = parse_str("?#????##??##???.#??# 4,2,6,1,1", false).unwrap(); // 997
         assert_eq!(2_u64, springs[0].get_arrangements());
 
-        let mut springs = parse(&vec!["?#???#?.#???? 6,1,1".to_owned()], false).unwrap(); // 1000
+        let mut springs = parse_str("?#???#?.#???? 6,1,1", false).unwrap(); // 1000
         assert_eq!(6_u64, springs[0].get_arrangements());
     }
 
     #[test]
     fn unfold_small() {
-        let mut springs = parse(&vec!["???.### 1,1,3".to_owned()], true).unwrap();
+        let mut springs = parse_str("???.### 1,1,3", true).unwrap();
         assert_eq!(1_u64, springs[0].get_arrangements());
     }
 
-    /*
     #[test]
     fn unfold_example() {
-        let mut springs = parse(&example(), true).unwrap();
+        let springs = parse_str(example(), true).unwrap();
         assert_eq!(
             vec![1, 16384, 1, 16, 2500, 506250],
             springs
-                .iter_mut()
-                .map(|s| s.get_arrangements())
+                .iter()
+                .map(|s| s.count_arrangements())
                 .collect::<Vec<_>>()
         );
     }
-    */
+
+    proptest! {
+        #[test]
+        fn counting_agrees_with_enumerating(
+            springs in "[.#?]{1,12}",
+            groups in vec(1..5_usize, 1..5),
+            unfold in proptest::bool::weighted(0.1),
+        ) {
+            // Unfolding multiplies the unknown springs by five, keep enumerating feasible
+            prop_assume!(!unfold || springs.matches('?').count() <= 2);
+            let groups = groups.iter().map(|g| g.to_string()).collect::<Vec<_>>().join(",");
+            let mut spring = Spring::new(springs, groups, unfold).unwrap();
+            prop_assert_eq!(spring.count_arrangements(), spring.get_arrangements());
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            parse_str("???.### 1,x,3", false).unwrap_err(),
+            ParseError::InvalidNumber { column: 11, .. }
+        ));
+        assert!(matches!(
+            parse_str("???.#!# 1,1,3", false).unwrap_err(),
+            ParseError::Unexpected { column: 6, .. }
+        ));
+        assert!(matches!(
+            parse_str("???.###", false).unwrap_err(),
+            ParseError::Unexpected { column: 8, .. }
+        ));
+        assert!(matches!(
+            parse_str("???.### 1,1,3000000000000000000000", false).unwrap_err(),
+            ParseError::InvalidNumber { column: 13, .. }
+        ));
+        assert!(matches!(
+            parse_str("???.### 1,0,3", false).unwrap_err(),
+            ParseError::Unexpected { column: 11, .. }
+        ));
+    }
 }
@@ -0,0 +1,268 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::{Context, Result};
+use std::env;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::error::{InputLine, ParseError, ParseResult};
+
+/// Environment variable pointing to a directory containing the `input-NN.txt` files
+pub const INPUTS_DIR_VAR: &str = "AOC_INPUTS_DIR";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    /// Look up `input-NN.txt` for the given day
+    Default,
+    Stdin,
+    Path(PathBuf),
+}
+
+impl InputSource {
+    /// Interprets a command-line argument, `-` meaning stdin
+    pub fn from_arg(arg: &str) -> InputSource {
+        if arg == "-" {
+            InputSource::Stdin
+        } else {
+            InputSource::Path(PathBuf::from(arg))
+        }
+    }
+
+    /// Opens the input of `day` for streaming it into a parser
+    pub fn open(&self, day: u8) -> Result<Box<dyn BufRead>> {
+        match self {
+            InputSource::Default => Ok(Box::new(open_file(&default_path(day))?)),
+            InputSource::Stdin => Ok(Box::new(io::stdin().lock())),
+            InputSource::Path(path) => Ok(Box::new(open_file(path)?)),
+        }
+    }
+
+    /// Reads the whole input of `day`, for parsing it more than once
+    pub fn read(&self, day: u8) -> Result<String> {
+        let mut text = String::new();
+        self.open(day)?.read_to_string(&mut text)?;
+        Ok(text)
+    }
+}
+
+pub fn file_name(day: u8) -> String {
+    format!("input-{:02}.txt", day)
+}
+
+/// `$AOC_INPUTS_DIR/input-NN.txt` if the variable is set, otherwise the file next to
+/// `Cargo.toml`, independent of the current working directory.
+pub fn default_path(day: u8) -> PathBuf {
+    let dir = match env::var_os(INPUTS_DIR_VAR) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env!("CARGO_MANIFEST_DIR")),
+    };
+    dir.join(file_name(day))
+}
+
+pub fn open_file(path: &Path) -> Result<BufReader<File>> {
+    let f = File::open(path).with_context(|| format!("Could not open {}", path.display()))?;
+    Ok(BufReader::new(f))
+}
+
+/// Reads an input one line at a time, numbering the lines for error reporting. Only the
+/// current line is kept in memory.
+pub struct LineReader<R> {
+    day: u8,
+    reader: R,
+    /// Index of the next line, starting at 0
+    index: usize,
+    buffer: String,
+}
+
+impl<R: BufRead> LineReader<R> {
+    pub fn new(day: u8, reader: R) -> LineReader<R> {
+        LineReader {
+            day,
+            reader,
+            index: 0,
+            buffer: String::new(),
+        }
+    }
+
+    /// The next line without its line ending, `None` at the end of the input
+    pub fn next_line(&mut self) -> ParseResult<Option<InputLine<'_>>> {
+        self.buffer.clear();
+        let read = self
+            .reader
+            .read_line(&mut self.buffer)
+            .map_err(|e| ParseError::Io {
+                day: self.day,
+                line: self.index + 1,
+                message: e.to_string(),
+            })?;
+        if read == 0 {
+            return Ok(None);
+        }
+        if self.buffer.ends_with('\n') {
+            self.buffer.pop();
+            if self.buffer.ends_with('\r') {
+                self.buffer.pop();
+            }
+        }
+        self.index += 1;
+        Ok(Some(InputLine::new(self.day, self.index - 1, &self.buffer)))
+    }
+
+    /// The next line which is not empty
+    pub fn next_non_empty(&mut self) -> ParseResult<Option<InputLine<'_>>> {
+        loop {
+            if self.next_line()?.is_none() {
+                return Ok(None);
+            }
+            if !self.buffer.is_empty() {
+                return Ok(Some(InputLine::new(self.day, self.index - 1, &self.buffer)));
+            }
+        }
+    }
+
+    /// Error for a line which should have followed the last one read
+    pub fn missing(&self, expected: &'static str) -> ParseError {
+        ParseError::MissingLine {
+            day: self.day,
+            line: self.index + 1,
+            expected,
+        }
+    }
+}
+
+/// Passes an input through unchanged while computing its SHA-256, so results can be
+/// traced back to the input which produced them
+pub struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: BufRead> HashingReader<R> {
+    pub fn new(inner: R) -> HashingReader<R> {
+        HashingReader {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// Reads whatever the parser did not consume and returns the hash of the whole
+    /// input as lowercase hex
+    pub fn finish(mut self) -> io::Result<String> {
+        io::copy(&mut self, &mut io::sink())?;
+        Ok(format!("{:x}", self.hasher.finalize()))
+    }
+}
+
+impl<R: BufRead> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for HashingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // The buffer is still filled from the preceding fill_buf, so this does not read
+        if let Ok(buf) = self.inner.fill_buf() {
+            self.hasher.update(&buf[..amt.min(buf.len())]);
+        }
+        self.inner.consume(amt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_arg() {
+        assert_eq!(InputSource::Stdin, InputSource::from_arg("-"));
+        assert_eq!(
+            InputSource::Path(PathBuf::from("foo/input.txt")),
+            InputSource::from_arg("foo/input.txt")
+        );
+    }
+
+    #[test]
+    fn default_path_in_crate_root() {
+        if env::var_os(INPUTS_DIR_VAR).is_none() {
+            assert_eq!(
+                Path::new(env!("CARGO_MANIFEST_DIR")).join("input-05.txt"),
+                default_path(5)
+            );
+        }
+    }
+
+    #[test]
+    fn line_reader() {
+        let mut lines = LineReader::new(3, "a\r\n\nb".as_bytes());
+        let line = lines.next_line().unwrap().unwrap();
+        assert_eq!((0, "a"), (line.index, line.text));
+        assert_eq!("", lines.next_line().unwrap().unwrap().text);
+        let line = lines.next_line().unwrap().unwrap();
+        assert_eq!((2, "b"), (line.index, line.text));
+        assert!(lines.next_line().unwrap().is_none());
+        assert_eq!(
+            ParseError::MissingLine {
+                day: 3,
+                line: 4,
+                expected: "more"
+            },
+            lines.missing("more")
+        );
+
+        let mut lines = LineReader::new(3, "\n\nc\n".as_bytes());
+        let line = lines.next_non_empty().unwrap().unwrap();
+        assert_eq!((2, "c"), (line.index, line.text));
+        assert!(lines.next_non_empty().unwrap().is_none());
+    }
+
+    #[test]
+    fn line_reader_invalid_utf8() {
+        let mut lines = LineReader::new(3, &b"ok\n\xff\n"[..]);
+        assert!(lines.next_line().unwrap().is_some());
+        assert!(matches!(
+            lines.next_line(),
+            Err(ParseError::Io {
+                day: 3,
+                line: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn hashing_reader() {
+        let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(abc, HashingReader::new("abc".as_bytes()).finish().unwrap());
+
+        // Partially parsed through the BufRead interface
+        let mut reader = HashingReader::new(BufReader::with_capacity(2, "abc".as_bytes()));
+        let mut first = String::new();
+        reader.read_line(&mut first).unwrap();
+        assert_eq!("abc", first);
+        assert_eq!(abc, reader.finish().unwrap());
+
+        let whole = HashingReader::new("a\nbc".as_bytes()).finish().unwrap();
+        let mut reader = HashingReader::new("a\nbc".as_bytes());
+        assert_eq!(
+            "a",
+            LineReader::new(0, &mut reader)
+                .next_line()
+                .unwrap()
+                .unwrap()
+                .text
+        );
+        assert_eq!(whole, reader.finish().unwrap());
+    }
+}
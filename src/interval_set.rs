@@ -0,0 +1,231 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use num::Num;
+use std::ops::Range;
+
+/// A set of values stored as half-open ranges.
+///
+/// The ranges are kept sorted, non-empty, and with a gap between any two of them, so
+/// two sets holding the same values compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T = i64> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet { ranges: Vec::new() }
+    }
+}
+
+impl<T: Clone + Ord> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet::default()
+    }
+
+    /// Adds every value of `range`
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.start >= range.end {
+            return;
+        }
+        // Ranges that overlap or touch `range` are merged into it
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = if first < last {
+            let start = range.start.min(self.ranges[first].start.clone());
+            let end = range.end.max(self.ranges[last - 1].end.clone());
+            start..end
+        } else {
+            range
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let index = self.ranges.partition_point(|r| r.end <= *value);
+        self.ranges.get(index).is_some_and(|r| r.contains(value))
+    }
+
+    /// The ranges in ascending order
+    pub fn iter(&self) -> std::slice::Iter<'_, Range<T>> {
+        self.ranges.iter()
+    }
+
+    /// Smallest value in the set
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start.clone())
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = self.clone();
+        for range in other.iter() {
+            result.insert(range.clone());
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = IntervalSet::new();
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            let start = x.start.clone().max(y.start.clone());
+            let end = x.end.clone().min(y.end.clone());
+            result.insert(start..end);
+            // The range ending first cannot overlap anything further
+            if x.end < y.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        result
+    }
+
+    /// Values of this set that are not in `other`
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = IntervalSet::new();
+        for range in self.iter() {
+            let mut start = range.start.clone();
+            let first = other.ranges.partition_point(|r| r.end <= range.start);
+            for cut in other.ranges[first..]
+                .iter()
+                .take_while(|r| r.start < range.end)
+            {
+                result.insert(start.clone()..cut.start.clone());
+                start = start.max(cut.end.clone());
+            }
+            result.insert(start..range.end.clone());
+        }
+        result
+    }
+}
+
+impl<T: Clone + Ord + Num> IntervalSet<T> {
+    /// Largest value in the set
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last().map(|r| r.end.clone() - T::one())
+    }
+
+    /// Number of values in the set
+    pub fn total_length(&self) -> T {
+        self.ranges.iter().fold(T::zero(), |total, r| {
+            total + (r.end.clone() - r.start.clone())
+        })
+    }
+
+    /// Builds the set from `(start, length)` pairs as they appear in puzzle inputs
+    pub fn from_pairs(pairs: &[(T, T)]) -> IntervalSet<T> {
+        pairs
+            .iter()
+            .map(|(start, length)| start.clone()..start.clone() + length.clone())
+            .collect()
+    }
+}
+
+impl<T: Clone + Ord> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut result = IntervalSet::new();
+        for range in iter {
+            result.insert(range);
+        }
+        result
+    }
+}
+
+impl<T> IntoIterator for IntervalSet<T> {
+    type Item = Range<T>;
+    type IntoIter = std::vec::IntoIter<Range<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a IntervalSet<T> {
+    type Item = &'a Range<T>;
+    type IntoIter = std::slice::Iter<'a, Range<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn normalize() {
+        let set: IntervalSet = [5..8, 0..2, 2..3, 7..10, 12..12, 20..25, 1..2]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            vec![0..3, 5..10, 20..25],
+            set.iter().cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(0), set.min());
+        assert_eq!(Some(24), set.max());
+        assert_eq!(13, set.total_length());
+        assert!(set.contains(&9));
+        assert!(!set.contains(&10));
+        assert_eq!(set, IntervalSet::from_pairs(&[(0, 3), (5, 5), (20, 5)]));
+
+        let empty = IntervalSet::<i64>::new();
+        assert!(empty.is_empty());
+        assert_eq!(None, empty.min());
+        assert_eq!(None, empty.max());
+        assert_eq!(0, empty.total_length());
+    }
+
+    #[test]
+    fn operations() {
+        let a: IntervalSet = [0..10, 20..30].into_iter().collect();
+        let b = IntervalSet::from_pairs(&[(5, 20)]);
+        assert_eq!(IntervalSet::from_pairs(&[(0, 30)]), a.union(&b));
+        assert_eq!(
+            vec![5..10, 20..25],
+            a.intersection(&b).into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0..5, 25..30],
+            a.difference(&b).into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(IntervalSet::from_pairs(&[(10, 10)]), b.difference(&a));
+    }
+
+    fn any_set() -> impl Strategy<Value = IntervalSet> {
+        vec((-10..40_i64, 0..8_i64), 0..5)
+            .prop_map(|ranges| ranges.into_iter().map(|(s, l)| s..s + l).collect())
+    }
+
+    fn values(set: &IntervalSet) -> BTreeSet<i64> {
+        set.iter().flat_map(|r| r.clone()).collect()
+    }
+
+    proptest! {
+        #[test]
+        fn agrees_with_sets_of_values(a in any_set(), b in any_set()) {
+            let (x, y) = (values(&a), values(&b));
+            prop_assert_eq!(values(&a.union(&b)), x.union(&y).copied().collect());
+            prop_assert_eq!(values(&a.intersection(&b)), x.intersection(&y).copied().collect());
+            prop_assert_eq!(values(&a.difference(&b)), x.difference(&y).copied().collect());
+            prop_assert_eq!(a.total_length(), x.len() as i64);
+            prop_assert_eq!(a.min(), x.first().copied());
+            prop_assert_eq!(a.max(), x.last().copied());
+            for w in a.iter().collect::<Vec<_>>().windows(2) {
+                prop_assert!(w[0].start < w[0].end && w[0].end < w[1].start);
+            }
+        }
+    }
+}
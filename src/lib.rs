@@ -2,14 +2,26 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 pub mod almanac;
+pub mod almanac_plot;
+pub mod answers;
+pub mod bench;
 pub mod boat_race;
 pub mod camel_cards;
 pub mod cosmic_expansion;
 pub mod cubebag;
+pub mod error;
 pub mod gear_ratios;
+pub mod grid;
 pub mod haunted_wasteland;
 pub mod hot_springs;
+pub mod input;
+pub mod interval_set;
 pub mod oasis;
+pub mod output;
 pub mod pipe_maze;
+pub mod pipe_maze_render;
+pub mod runner;
+pub mod scaffold;
 pub mod scratch_cards;
+pub mod solution;
 pub mod trebuchet;
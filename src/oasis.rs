@@ -3,7 +3,12 @@
 
 // 2023-09
 
-use anyhow::{Ok, Result};
+use anyhow::Result;
+use std::io::BufRead;
+
+use crate::error::ParseResult;
+use crate::input::LineReader;
+use crate::solution::Solution;
 
 #[derive(Debug, Clone)]
 pub struct History {
@@ -30,7 +35,7 @@ impl History {
 
     pub fn predict_future(&self) -> i64 {
         let future = History::recurse(&self.readings);
-        *future.iter().rev().next().unwrap()
+        *future.iter().next_back().unwrap()
     }
 
     fn recurse_past(values: &[i64]) -> Vec<i64> {
@@ -56,34 +61,61 @@ impl History {
     }
 }
 
-pub fn parse(lines: &[String]) -> Result<Vec<History>> {
+pub fn parse<R: BufRead>(input: R) -> ParseResult<Vec<History>> {
     let mut histories = Vec::new();
-    for line in lines {
+    let mut lines = LineReader::new(Day09::DAY, input);
+    while let Some(line) = lines.next_line()? {
         let readings = line
+            .text
             .split(' ')
-            .filter_map(|s| s.parse::<i64>().ok())
-            .collect::<Vec<_>>();
+            .filter(|s| !s.is_empty())
+            .map(|s| line.number::<i64>(s))
+            .collect::<ParseResult<Vec<_>>>()?;
         histories.push(History { readings });
     }
     Ok(histories)
 }
 
+pub fn parse_str(text: &str) -> ParseResult<Vec<History>> {
+    parse(text.as_bytes())
+}
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+    const NAME: &'static str = "oasis";
+
+    type Input = Vec<History>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i64> {
+        Ok(input.iter().map(|h| h.predict_future()).sum())
+    }
+
+    fn part2(input: &Self::Input) -> Result<i64> {
+        Ok(input.iter().map(|h| h.predict_past()).sum())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn example() -> Vec<String> {
+    fn example() -> &'static str {
         r#"0 3 6 9 12 15
 1 3 6 10 15 21
 10 13 16 21 30 45"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
     #[test]
     fn result_web() {
-        let histories = parse(&example()).unwrap();
+        let histories = parse_str(example()).unwrap();
         assert_eq!(
             114,
             histories.iter().map(|h| h.predict_future()).sum::<i64>()
@@ -92,8 +124,7 @@ mod tests {
 
     #[test]
     fn puzzle_input_sample() {
-        let input = vec!["25 50 95 171 295 490 783 1201 1765 2482 3335 4271 5187 5914 6199 5685 3889 178 -6257 -16429 -31585".to_owned()];
-        let histories = parse(&input).unwrap();
+        let histories = parse_str("25 50 95 171 295 490 783 1201 1765 2482 3335 4271 5187 5914 6199 5685 3889 178 -6257 -16429 -31585").unwrap();
         assert_eq!(
             -53238,
             histories.iter().map(|h| h.predict_future()).sum::<i64>()
@@ -102,14 +133,13 @@ mod tests {
 
     #[test]
     fn result_web_part2() {
-        let input = vec!["10  13  16  21  30  45".to_owned()];
-        let histories = parse(&input).unwrap();
+        let histories = parse_str("10  13  16  21  30  45").unwrap();
         assert_eq!(5, histories.iter().map(|h| h.predict_past()).sum::<i64>());
     }
 
     #[test]
     fn result_web_part2_all() {
-        let histories = parse(&example()).unwrap();
+        let histories = parse_str(example()).unwrap();
         assert_eq!(2, histories.iter().map(|h| h.predict_past()).sum::<i64>());
     }
 }
@@ -0,0 +1,210 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::{bail, Result};
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::solution::Part;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// `part N: answer`, for reading in a terminal
+    #[default]
+    Plain,
+    /// One array holding an object per answer
+    Json,
+    /// A header line, then one line per answer
+    Csv,
+}
+
+impl Format {
+    pub fn from_arg(arg: &str) -> Result<Format> {
+        match arg {
+            "plain" => Ok(Format::Plain),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => bail!("Invalid format {arg}, expected plain, json or csv"),
+        }
+    }
+}
+
+/// One answer together with how it was obtained
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub day: u8,
+    pub name: &'static str,
+    pub part: Part,
+    pub answer: String,
+    /// Time spent solving the part, without parsing
+    pub duration: Duration,
+    /// SHA-256 of the puzzle input as lowercase hex
+    pub input_hash: String,
+}
+
+const CSV_HEADER: &str = "day,name,part,answer,duration_ns,input_hash";
+
+/// Writes records as they are produced, so that long runs show results early
+pub struct Output<W: Write> {
+    out: W,
+    format: Format,
+    /// Print a `day NN (name)` line before the answers of each day in plain format
+    day_headers: bool,
+    last_day: Option<u8>,
+    written: usize,
+}
+
+impl<W: Write> Output<W> {
+    pub fn new(out: W, format: Format, day_headers: bool) -> Output<W> {
+        Output {
+            out,
+            format,
+            day_headers,
+            last_day: None,
+            written: 0,
+        }
+    }
+
+    pub fn begin(&mut self) -> io::Result<()> {
+        match self.format {
+            Format::Plain => Ok(()),
+            Format::Json => write!(self.out, "["),
+            Format::Csv => writeln!(self.out, "{CSV_HEADER}"),
+        }
+    }
+
+    pub fn write(&mut self, record: &Record) -> io::Result<()> {
+        match self.format {
+            Format::Plain => {
+                if self.day_headers && self.last_day != Some(record.day) {
+                    writeln!(self.out, "day {:02} ({})", record.day, record.name)?;
+                }
+                writeln!(self.out, "part {}: {}", record.part, record.answer)?;
+            }
+            Format::Json => {
+                let object = serde_json::json!({
+                    "day": record.day,
+                    "name": record.name,
+                    "part": record.part.number(),
+                    "answer": record.answer,
+                    "duration_ns": record.duration.as_nanos() as u64,
+                    "input_hash": record.input_hash,
+                });
+                let separator = if self.written == 0 { "" } else { "," };
+                write!(self.out, "{separator}\n  {object}")?;
+            }
+            Format::Csv => {
+                writeln!(
+                    self.out,
+                    "{},{},{},{},{},{}",
+                    record.day,
+                    record.name,
+                    record.part,
+                    csv_field(&record.answer),
+                    record.duration.as_nanos(),
+                    record.input_hash
+                )?;
+            }
+        }
+        self.last_day = Some(record.day);
+        self.written += 1;
+        self.out.flush()
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.format == Format::Json {
+            if self.written > 0 {
+                writeln!(self.out)?;
+            }
+            writeln!(self.out, "]")?;
+        }
+        self.out.flush()
+    }
+}
+
+/// Quotes `field` if it would otherwise break the CSV line
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<Record> {
+        let record = |day, name, part, answer: &str| Record {
+            day,
+            name,
+            part,
+            answer: answer.to_string(),
+            duration: Duration::from_micros(15),
+            input_hash: format!("{:02}ff", day),
+        };
+        vec![
+            record(1, "trebuchet", Part::One, "142"),
+            record(1, "trebuchet", Part::Two, "281"),
+            record(2, "cubebag", Part::One, "8"),
+        ]
+    }
+
+    fn render(format: Format, day_headers: bool, records: &[Record]) -> String {
+        let mut text = Vec::new();
+        let mut output = Output::new(&mut text, format, day_headers);
+        output.begin().unwrap();
+        for record in records {
+            output.write(record).unwrap();
+        }
+        output.finish().unwrap();
+        String::from_utf8(text).unwrap()
+    }
+
+    #[test]
+    fn plain() {
+        assert_eq!(
+            "part 1: 142\npart 2: 281\n",
+            render(Format::Plain, false, &records()[..2])
+        );
+        assert_eq!(
+            "day 01 (trebuchet)\npart 1: 142\npart 2: 281\nday 02 (cubebag)\npart 1: 8\n",
+            render(Format::Plain, true, &records())
+        );
+    }
+
+    #[test]
+    fn json() {
+        let text = render(Format::Json, true, &records());
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        let records = value.as_array().unwrap();
+        assert_eq!(3, records.len());
+        assert_eq!(
+            serde_json::json!({
+                "day": 1,
+                "name": "trebuchet",
+                "part": 2,
+                "answer": "281",
+                "duration_ns": 15000,
+                "input_hash": "01ff",
+            }),
+            records[1]
+        );
+        assert_eq!("[]\n", render(Format::Json, true, &[]));
+    }
+
+    #[test]
+    fn csv() {
+        let mut records = records();
+        records[2].answer = "a,\"b\"".to_string();
+        assert_eq!(
+            "day,name,part,answer,duration_ns,input_hash\n\
+             1,trebuchet,1,142,15000,01ff\n\
+             1,trebuchet,2,281,15000,01ff\n\
+             2,cubebag,1,\"a,\"\"b\"\"\",15000,02ff\n",
+            render(Format::Csv, true, &records)
+        );
+        assert!(Format::from_arg("xml").is_err());
+    }
+}
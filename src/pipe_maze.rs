@@ -3,237 +3,333 @@
 
 // 2023-10
 
-use anyhow::{bail, Context, Ok, Result};
-use std::collections::{HashMap, HashSet};
+use anyhow::{bail, Context, Result};
+use std::collections::HashSet;
+use std::io::BufRead;
+use thiserror::Error;
+
+use crate::error::{ParseError, ParseResult};
+use crate::grid::{Direction, Grid, Position};
+use crate::solution::Solution;
 
 #[derive(Debug, Clone)]
 pub struct LoopNode {
-    pub location: (usize, usize),
+    pub location: Position,
     pub distance: usize,
 }
 
+/// Why no loop can be traced from the start
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MazeError {
+    #[error("No start found")]
+    NoStart,
+    #[error("No loop of pipes leads from the start at {start:?} back to it")]
+    DeadEnd { start: Position },
+    #[error("Several loops lead through the start at {start:?}, it could be any of {shapes:?}")]
+    Ambiguous { start: Position, shapes: Vec<char> },
+}
+
+/// Directions a pipe connects, empty for ground and the start
+pub fn connections(pipe: char) -> &'static [Direction] {
+    match pipe {
+        '|' => &[Direction::Up, Direction::Down],
+        '-' => &[Direction::Left, Direction::Right],
+        'L' => &[Direction::Up, Direction::Right],
+        'J' => &[Direction::Up, Direction::Left],
+        '7' => &[Direction::Down, Direction::Left],
+        'F' => &[Direction::Down, Direction::Right],
+        _ => &[],
+    }
+}
+
+/// The pipe connecting `a` and `b`
+fn pipe_between(a: Direction, b: Direction) -> Option<char> {
+    "|-LJ7F".chars().find(|&pipe| {
+        let connected = connections(pipe);
+        a != b && connected.contains(&a) && connected.contains(&b)
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentKind {
+    /// Every pipe connects to two others
+    Loop,
+    /// Pipes connected one after the other with two loose ends
+    Chain,
+}
+
+/// Pipes connected to each other, and to no pipe outside
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipeComponent {
+    pub kind: ComponentKind,
+    /// Row by row
+    pub tiles: Vec<Position>,
+    /// Top left and bottom right corner of the smallest rectangle holding every tile
+    pub bounds: (Position, Position),
+}
+
+impl PipeComponent {
+    /// Number of pipes
+    pub fn length(&self) -> usize {
+        self.tiles.len()
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Maze {
-    pub maze: HashMap<(usize, usize), String>,
+    pub maze: Grid<char>,
     pub start: Option<LoopNode>,
+    /// Every tile of the loop in the order it is walked, ending with the start
     pub big_loop: Vec<LoopNode>,
-    pub loop_hash: HashSet<(usize, usize)>,
-    pub start_out: (usize, usize),
-    pub start_is: String,
+    pub loop_hash: HashSet<Position>,
+    /// The pipe hidden under the start
+    pub start_is: char,
 }
 
 impl Maze {
-    pub fn add(&mut self, location: (usize, usize), pipe: String) {
-        self.maze.insert(location, pipe.clone());
-        if pipe == "S" {
-            self.start = Some(LoopNode {
+    /// Follows the pipes from `start` towards `out` one tile at a time. Returns the
+    /// tiles passed and the direction the walk comes back to `start` from, or `None` if
+    /// the pipes lead off the grid or into one not connected to them.
+    fn follow(&self, start: Position, out: Direction) -> Option<(Vec<Position>, Direction)> {
+        let mut path = Vec::new();
+        let mut position = start;
+        let mut direction = out;
+        loop {
+            position = self.maze.step(position, direction)?;
+            let entered_from = direction.opposite();
+            if position == start {
+                return Some((path, entered_from));
+            }
+            // Each pipe connects two tiles, so without coming back to the start the
+            // walk cannot run into a circle
+            let connected = connections(*self.maze.get(position)?);
+            if !connected.contains(&entered_from) {
+                return None;
+            }
+            direction = *connected.iter().find(|&&d| d != entered_from)?;
+            path.push(position);
+        }
+    }
+
+    /// Finds the loop through the start and the pipe the start stands for, trying every
+    /// direction out of it
+    pub fn trace_loop(&mut self) -> Result<(), MazeError> {
+        let start = self.start.as_ref().ok_or(MazeError::NoStart)?.location;
+        let mut loops: Vec<(char, Vec<Position>)> = Vec::new();
+        for out in Direction::all() {
+            if let Some((path, back)) = self.follow(start, out) {
+                let shape = pipe_between(out, back).ok_or(MazeError::DeadEnd { start })?;
+                // Walking the other way round finds the same loop again
+                if !loops.iter().any(|(s, _)| *s == shape) {
+                    loops.push((shape, path));
+                }
+            }
+        }
+        let (shape, path) = match loops.len() {
+            0 => return Err(MazeError::DeadEnd { start }),
+            1 => loops.remove(0),
+            _ => {
+                return Err(MazeError::Ambiguous {
+                    start,
+                    shapes: loops.into_iter().map(|(s, _)| s).collect(),
+                })
+            }
+        };
+        self.start_is = shape;
+        self.big_loop = path
+            .into_iter()
+            .chain([start])
+            .enumerate()
+            .map(|(i, location)| LoopNode {
                 location,
-                distance: 0,
-            });
+                distance: i + 1,
+            })
+            .collect();
+        self.loop_hash = self.big_loop.iter().map(|n| n.location).collect();
+        Ok(())
+    }
+
+    /// The pipe at `pos`. The start is the pipe found by `trace_loop`, and ground before
+    /// it has run.
+    pub fn pipe_at(&self, pos: Position) -> Option<char> {
+        match *self.maze.get(pos)? {
+            'S' => Some(self.start_is).filter(|c| connections(*c).len() == 2),
+            c => Some(c),
         }
     }
 
-    pub fn walk(&mut self, location: LoopNode, from: Option<(usize, usize)>) {
-        let symbol = self.maze.get(&location.location).unwrap();
-        if let Some(from) = from {
-            if symbol.as_str() == "S" {
-                self.start_is = if location.location.0 > from.0 {
-                    match self.start_out {
-                        (0, 1) => "L".to_string(),
-                        (1, 0) => "|".to_string(),
-                        (0, 5) => "J".to_string(),
-                        _ => todo!("error"),
-                    }
-                } else if location.location.0 < from.0 {
-                    match self.start_out {
-                        (0, 1) => "F".to_string(),
-                        (5, 0) => "|".to_string(),
-                        (0, 5) => "7".to_string(),
-                        _ => todo!("error"),
-                    }
-                } else if location.location.1 > from.1 {
-                    match self.start_out {
-                        (0, 1) => "-".to_string(),
-                        (1, 0) => "7".to_string(),
-                        (5, 0) => "J".to_string(),
-                        _ => todo!("error"),
-                    }
-                } else if location.location.1 < from.1 {
-                    match self.start_out {
-                        (1, 0) => "F".to_string(),
-                        (5, 0) => "L".to_string(),
-                        (0, 5) => "-".to_string(),
-                        _ => todo!("error"),
-                    }
-                } else {
-                    "".to_string()
-                };
-                return;
+    /// Neighbours of `pos` whose pipe connects to the one at `pos`
+    fn links(&self, pos: Position) -> Vec<Position> {
+        let Some(pipe) = self.pipe_at(pos) else {
+            return Vec::new();
+        };
+        connections(pipe)
+            .iter()
+            .filter_map(|&d| {
+                let next = self.maze.step(pos, d)?;
+                let other = self.pipe_at(next)?;
+                connections(other).contains(&d.opposite()).then_some(next)
+            })
+            .collect()
+    }
+
+    /// Every group of connected pipes, ordered by their first tile. Pipes connect only
+    /// when both point at each other, so each pipe has at most two neighbours and a
+    /// component is either a loop or a chain.
+    pub fn components(&self) -> Vec<PipeComponent> {
+        let mut seen = HashSet::new();
+        let mut result = Vec::new();
+        for pos in self.maze.positions() {
+            if seen.contains(&pos) || self.pipe_at(pos).is_none_or(|c| connections(c).is_empty()) {
+                continue;
             }
-            let next = match symbol.as_str() {
-                "|" => {
-                    if from.0 < location.location.0 {
-                        LoopNode {
-                            location: (location.location.0 + 1, location.location.1),
-                            distance: location.distance + 1,
-                        }
-                    } else {
-                        LoopNode {
-                            location: (location.location.0 - 1, location.location.1),
-                            distance: location.distance + 1,
-                        }
-                    }
-                }
-                "-" => {
-                    if from.1 < location.location.1 {
-                        LoopNode {
-                            location: (location.location.0, location.location.1 + 1),
-                            distance: location.distance + 1,
-                        }
-                    } else {
-                        LoopNode {
-                            location: (location.location.0, location.location.1 - 1),
-                            distance: location.distance + 1,
-                        }
-                    }
-                }
-                "L" => {
-                    if from.0 < location.location.0 {
-                        LoopNode {
-                            location: (location.location.0, location.location.1 + 1),
-                            distance: location.distance + 1,
-                        }
-                    } else {
-                        LoopNode {
-                            location: (location.location.0 - 1, location.location.1),
-                            distance: location.distance + 1,
-                        }
+            let mut tiles = Vec::new();
+            let mut closed = true;
+            let mut todo = vec![pos];
+            seen.insert(pos);
+            while let Some(tile) = todo.pop() {
+                let links = self.links(tile);
+                closed &= links.len() == 2;
+                for next in links {
+                    if seen.insert(next) {
+                        todo.push(next);
                     }
                 }
-                "J" => {
-                    if from.0 < location.location.0 {
-                        LoopNode {
-                            location: (location.location.0, location.location.1 - 1),
-                            distance: location.distance + 1,
-                        }
-                    } else {
-                        LoopNode {
-                            location: (location.location.0 - 1, location.location.1),
-                            distance: location.distance + 1,
-                        }
-                    }
-                }
-                "7" => {
-                    if from.0 > location.location.0 {
-                        LoopNode {
-                            location: (location.location.0, location.location.1 - 1),
-                            distance: location.distance + 1,
-                        }
-                    } else {
-                        LoopNode {
-                            location: (location.location.0 + 1, location.location.1),
-                            distance: location.distance + 1,
-                        }
-                    }
-                }
-                "F" => {
-                    if from.0 > location.location.0 {
-                        LoopNode {
-                            location: (location.location.0, location.location.1 + 1),
-                            distance: location.distance + 1,
-                        }
-                    } else {
-                        LoopNode {
-                            location: (location.location.0 + 1, location.location.1),
-                            distance: location.distance + 1,
-                        }
-                    }
-                }
-                _ => todo!("huhu"),
-            };
-            self.big_loop.push(next.clone());
-            self.loop_hash.insert(next.location);
-            self.walk(next, Some(location.location));
-        } else {
-            // Start of maze
-            if let Some(right) = self
-                .maze
-                .get(&(location.location.0, location.location.1 + 1))
-            {
-                if right == "-" || right == "J" || right == "7" {
-                    let next = LoopNode {
-                        location: (location.location.0, location.location.1 + 1),
-                        distance: 1,
-                    };
-                    self.big_loop.push(next.clone());
-                    self.loop_hash.insert(next.location);
-                    self.start_out = (0, 1);
-                    self.walk(next, Some(location.location));
-                }
-            } else if let Some(down) = self
-                .maze
-                .get(&(location.location.0 + 1, location.location.1))
-            {
-                if down == "|" || down == "J" || down == "L" {
-                    let next = LoopNode {
-                        location: (location.location.0 + 1, location.location.1),
-                        distance: 1,
-                    };
-                    self.big_loop.push(next.clone());
-                    self.loop_hash.insert(next.location);
-                    self.start_out = (1, 0);
-                    self.walk(next, Some(location.location));
-                }
+                tiles.push(tile);
             }
-            // same for left and up
+            tiles.sort();
+            let rows = tiles.iter().map(|t| t.0);
+            let columns = tiles.iter().map(|t| t.1);
+            let bounds = (
+                (
+                    rows.clone().min().unwrap_or(0),
+                    columns.clone().min().unwrap_or(0),
+                ),
+                (rows.max().unwrap_or(0), columns.max().unwrap_or(0)),
+            );
+            result.push(PipeComponent {
+                kind: if closed {
+                    ComponentKind::Loop
+                } else {
+                    ComponentKind::Chain
+                },
+                tiles,
+                bounds,
+            });
         }
+        result
     }
 
     pub fn furthest_distance(&mut self) -> Result<usize> {
-        if let Some(start) = &self.start {
-            self.walk(start.clone(), None);
-            Ok(self
-                .big_loop
+        self.trace_loop()?;
+        Ok(self.big_loop.last().context("No distance found")?.distance / 2)
+    }
+
+    /// Area the loop encloses, measured through the middle of its tiles with the
+    /// shoelace formula. Positive if the loop runs clockwise on screen, with rows
+    /// growing downwards, and negative if it runs anticlockwise.
+    pub fn signed_area(&self) -> Result<i64> {
+        if self.big_loop.is_empty() {
+            bail!("No loop traced");
+        }
+        let corner = |node: &LoopNode| -> Result<(i64, i64)> {
+            Ok((
+                i64::try_from(node.location.1)?,
+                i64::try_from(node.location.0)?,
+            ))
+        };
+        let mut twice = 0;
+        for (a, b) in self
+            .big_loop
+            .iter()
+            .zip(self.big_loop.iter().cycle().skip(1))
+        {
+            let ((x1, y1), (x2, y2)) = (corner(a)?, corner(b)?);
+            twice += x1 * y2 - x2 * y1;
+        }
+        // Steps along rows and columns enclose whole tiles
+        Ok(twice / 2)
+    }
+
+    /// Same as `count_inside`, from the traced loop alone: by Pick's theorem the area
+    /// holds one tile per point inside plus half a tile per loop tile, less one
+    pub fn count_inside_by_area(&self) -> Result<usize> {
+        let area = self.signed_area()?.unsigned_abs() as usize;
+        Ok(area + 1 - self.big_loop.len() / 2)
+    }
+
+    /// Tiles the loop encloses, found by flooding the outside. Each tile is blown up to
+    /// 3×3 cells with the loop's pipes drawn through their middle, so the flood can
+    /// squeeze between pipes that run side by side.
+    pub fn enclosed_tiles(&self) -> Result<HashSet<Position>> {
+        if self.big_loop.is_empty() {
+            bail!("No loop traced");
+        }
+        // A ring of cells around the grid lets the flood reach every side from one corner
+        let cell = |pos: Position| (3 * pos.0 + 2, 3 * pos.1 + 2);
+        let mut blocked = Grid::new(3 * self.maze.width() + 2, 3 * self.maze.height() + 2, false);
+        for &tile in &self.loop_hash {
+            let middle = cell(tile);
+            let pipe = self.pipe_at(tile).context("Loop runs over ground")?;
+            let arms = connections(pipe)
                 .iter()
-                .rev()
-                .next()
-                .context("No distance found")?
-                .distance
-                / 2)
-        } else {
-            bail!("No start found")
+                .filter_map(|&d| blocked.step(middle, d))
+                .collect::<Vec<_>>();
+            for next in arms.into_iter().chain([middle]) {
+                if let Some(wall) = blocked.get_mut(next) {
+                    *wall = true;
+                }
+            }
         }
+
+        let mut outside = Grid::new(blocked.width(), blocked.height(), false);
+        let mut todo = vec![(0, 0)];
+        while let Some(pos) = todo.pop() {
+            match outside.get_mut(pos) {
+                Some(seen @ false) if blocked.get(pos) == Some(&false) => *seen = true,
+                _ => continue,
+            }
+            todo.extend(blocked.neighbors4(pos));
+        }
+
+        Ok(self
+            .maze
+            .positions()
+            .filter(|pos| !self.loop_hash.contains(pos) && outside.get(cell(*pos)) == Some(&false))
+            .collect())
     }
 
-    pub fn count_inside(&self, lines: &[String]) -> Result<usize> {
+    pub fn count_inside(&self) -> Result<usize> {
         let mut inside = Vec::new();
         let mut our_maze = self.maze.clone();
-        if let Some(start) = our_maze.get_mut(&self.start.clone().unwrap().location) {
-            *start = self.start_is.clone();
+        if let Some(start) =
+            our_maze.get_mut(self.start.clone().context("No start found")?.location)
+        {
+            *start = self.start_is;
         }
         // Don't try to be smart
-        for (i, line) in lines.iter().enumerate() {
+        for i in 0..our_maze.height() {
             let mut is_inside = false;
             let mut last_was_l = false;
             let mut last_was_f = false;
-            for (j, _) in line.chars().enumerate() {
+            for j in 0..our_maze.width() {
                 let pos = (i, j);
                 if self.loop_hash.contains(&pos) {
-                    if our_maze.get(&pos).unwrap() == "|" {
+                    if our_maze.get(pos) == Some(&'|') {
                         is_inside = !is_inside;
-                    } else if our_maze.get(&pos).unwrap() == "L" {
+                    } else if our_maze.get(pos) == Some(&'L') {
                         last_was_l = true;
                         last_was_f = false;
-                    } else if our_maze.get(&pos).unwrap() == "7" {
+                    } else if our_maze.get(pos) == Some(&'7') {
                         if last_was_l {
                             is_inside = !is_inside;
                         }
                         last_was_l = false;
                         last_was_f = false;
-                    } else if our_maze.get(&pos).unwrap() == "F" {
+                    } else if our_maze.get(pos) == Some(&'F') {
                         last_was_f = true;
                         last_was_l = false;
-                    } else if our_maze.get(&pos).unwrap() == "J" {
+                    } else if our_maze.get(pos) == Some(&'J') {
                         if last_was_f {
                             is_inside = !is_inside;
                         }
@@ -249,45 +345,105 @@ impl Maze {
     }
 }
 
-pub fn parse(lines: &[String]) -> Result<Maze> {
-    let mut result = Maze::default();
-    for (i, line) in lines.iter().enumerate() {
-        for (j, column) in line.chars().enumerate() {
-            result.add((i, j), column.to_string());
+pub fn parse<R: BufRead>(input: R) -> ParseResult<Maze> {
+    let maze = Grid::parse(Day10::DAY, input, "pipe, ground or start", |c| {
+        if "|-LJ7F.S".contains(c) {
+            Some(c)
+        } else {
+            None
         }
+    })?;
+    let starts = maze
+        .iter()
+        .filter(|(_, c)| **c == 'S')
+        .map(|(pos, _)| pos)
+        .collect::<Vec<_>>();
+    if let Some(second) = starts.get(1) {
+        return Err(ParseError::Unexpected {
+            day: Day10::DAY,
+            line: second.0 + 1,
+            column: second.1 + 1,
+            expected: "only one start",
+            found: "S".to_string(),
+        });
     }
-    Ok(result)
+    Ok(Maze {
+        maze,
+        start: starts.first().map(|&location| LoopNode {
+            location,
+            distance: 0,
+        }),
+        ..Default::default()
+    })
+}
+
+pub fn parse_str(text: &str) -> ParseResult<Maze> {
+    parse(text.as_bytes())
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    const NAME: &'static str = "pipe_maze";
+
+    type Input = Maze;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<usize> {
+        input.clone().furthest_distance()
+    }
+
+    fn part2(input: &Self::Input) -> Result<usize> {
+        let mut maze = input.clone();
+        maze.furthest_distance()?;
+        maze.count_inside()
+    }
+}
+
+/// A loop squeezing between pipes, also rendered in the tests of `pipe_maze_render`
+#[cfg(test)]
+pub(crate) fn example_squeeze() -> &'static str {
+    r#"
+..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+.........."#
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn example_simple() -> Vec<String> {
+    fn example_simple() -> &'static str {
         r#"
 .....
 .S-7.
 .|.|.
 .L-J.
 ....."#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
-    fn example_dirty() -> Vec<String> {
+    fn example_dirty() -> &'static str {
         r#"
 7-F7-
 .FJ|7
 SJ.L7
 |F--J
 LJ.LJ"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
-    fn example_p2_small() -> Vec<String> {
+    fn example_p2_small() -> &'static str {
         r#"
 ...........
 .S-------7.
@@ -298,12 +454,9 @@ LJ.LJ"#
 .|..|.|..|.
 .L--J.L--J.
 ..........."#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
-    fn example_p2_large() -> Vec<String> {
+    fn example_p2_large() -> &'static str {
         r#"
 .F----7F7F7F7F-7....
 .|F--7||||||||FJ....
@@ -315,24 +468,17 @@ L--J.L7...LJS7F-7L7.
 .....|FJLJ|FJ|F7|.LJ
 ....FJL-7.||.||||...
 ....L---J.LJ.LJLJ..."#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
-    fn example_self() -> Vec<String> {
+    fn example_self() -> &'static str {
         r#"
 .F---7
 .||FFJ
 .|F-J.
 .SJ..."#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
-    #[allow(dead_code)]
-    fn example_p2_large_junk() -> Vec<String> {
+    fn example_p2_large_junk() -> &'static str {
         r#"
 FF7FSF7F7F7F7F7F---7
 L|LJ||||||||||||F--J
@@ -344,52 +490,251 @@ L---JF-JLJ.||-FJLJJ7
 7-L-JL7||F7|L7F-7F7|
 L.L7LFJ|||||FJL7||LJ
 L7JLJL-JLJLJL--JLJ.L"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
     #[test]
     fn result_web() {
-        let mut maze = parse(&example_simple()).unwrap();
+        let mut maze = parse_str(example_simple()).unwrap();
         assert_eq!(4, maze.furthest_distance().unwrap());
-        assert_eq!(1, maze.count_inside(&example_simple()).unwrap());
+        assert_eq!(1, maze.count_inside().unwrap());
     }
 
     #[test]
     fn result_web_dirty() {
-        let mut maze = parse(&example_dirty()).unwrap();
+        let mut maze = parse_str(example_dirty()).unwrap();
         assert_eq!(8, maze.furthest_distance().unwrap());
-        assert_eq!(1, maze.count_inside(&example_dirty()).unwrap());
+        assert_eq!(1, maze.count_inside().unwrap());
     }
 
     #[test]
     fn result_inner_small() {
-        let mut maze = parse(&example_p2_small()).unwrap();
+        let mut maze = parse_str(example_p2_small()).unwrap();
         let _ = maze.furthest_distance();
-        assert_eq!(4, maze.count_inside(&example_p2_small()).unwrap());
+        assert_eq!(4, maze.count_inside().unwrap());
     }
 
     #[test]
     fn result_inner_large() {
-        let mut maze = parse(&example_p2_large()).unwrap();
+        let mut maze = parse_str(example_p2_large()).unwrap();
         let _ = maze.furthest_distance();
-        assert_eq!(8, maze.count_inside(&example_p2_large()).unwrap());
+        assert_eq!(8, maze.count_inside().unwrap());
     }
 
     #[test]
     fn result_inner_self() {
-        let mut maze = parse(&example_self()).unwrap();
+        let mut maze = parse_str(example_self()).unwrap();
         let _ = maze.furthest_distance();
-        assert_eq!(2, maze.count_inside(&example_self()).unwrap());
+        assert_eq!(2, maze.count_inside().unwrap());
+    }
+
+    #[test]
+    fn start_shapes() {
+        // The start on each tile of the simple loop stands for the pipe it replaces
+        let lines = example_simple().trim().replace('S', "F");
+        let maze = parse_str(&lines).unwrap();
+        for ((row, column), pipe) in maze.maze.iter().filter(|(_, c)| "|-LJ7F".contains(**c)) {
+            let mut lines = lines.lines().map(String::from).collect::<Vec<_>>();
+            lines[row].replace_range(column..column + 1, "S");
+            let mut maze = parse_str(&lines.join("\n")).unwrap();
+            assert_eq!(4, maze.furthest_distance().unwrap());
+            assert_eq!(*pipe, maze.start_is);
+            assert_eq!(
+                Some((row, column)),
+                maze.big_loop.last().map(|n| n.location)
+            );
+            assert_eq!(8, maze.loop_hash.len());
+            assert_eq!(1, maze.count_inside().unwrap());
+        }
+    }
+
+    #[test]
+    fn area_agrees_with_scan() {
+        let snake = snake(20, 10);
+        for example in [
+            example_simple(),
+            example_dirty(),
+            example_p2_small(),
+            example_p2_large(),
+            example_self(),
+            example_p2_large_junk(),
+            &snake,
+        ] {
+            let mut maze = parse_str(example).unwrap();
+            maze.furthest_distance().unwrap();
+            assert_eq!(
+                maze.count_inside().unwrap(),
+                maze.count_inside_by_area().unwrap(),
+                "{example}"
+            );
+        }
+    }
+
+    #[test]
+    fn flood_agrees_with_scan() {
+        let snake = snake(20, 10);
+        for example in [
+            example_simple(),
+            example_dirty(),
+            example_p2_small(),
+            example_p2_large(),
+            example_squeeze(),
+            example_self(),
+            example_p2_large_junk(),
+            &snake,
+        ] {
+            let mut maze = parse_str(example).unwrap();
+            maze.furthest_distance().unwrap();
+            assert_eq!(
+                maze.count_inside().unwrap(),
+                maze.enclosed_tiles().unwrap().len(),
+                "{example}"
+            );
+        }
+    }
+
+    #[test]
+    fn enclosed_tiles() {
+        let mut maze = parse_str(example_squeeze()).unwrap();
+        assert!(maze.enclosed_tiles().is_err());
+        maze.furthest_distance().unwrap();
+        assert_eq!(
+            HashSet::from([(6, 2), (6, 3), (6, 6), (6, 7)]),
+            maze.enclosed_tiles().unwrap()
+        );
+
+        let mut maze = parse_str(example_dirty()).unwrap();
+        maze.furthest_distance().unwrap();
+        assert_eq!(HashSet::from([(2, 2)]), maze.enclosed_tiles().unwrap());
+    }
+
+    #[test]
+    fn signed_area() {
+        let mut maze = parse_str(example_simple()).unwrap();
+        assert!(maze.signed_area().is_err());
+        maze.furthest_distance().unwrap();
+        // Leaves the start downwards, so runs anticlockwise
+        assert_eq!((2, 1), maze.big_loop[0].location);
+        assert_eq!(-4, maze.signed_area().unwrap());
+
+        maze.big_loop.reverse();
+        assert_eq!(4, maze.signed_area().unwrap());
+        assert_eq!(1, maze.count_inside_by_area().unwrap());
+    }
+
+    #[test]
+    fn components_dirty() {
+        let mut maze = parse_str(example_dirty()).unwrap();
+        // Before tracing the start is ground and cuts the loop open
+        let untraced = maze.components();
+        assert_eq!(6, untraced.len());
+        assert_eq!(ComponentKind::Chain, untraced[2].kind);
+        assert_eq!(15, untraced[2].length());
+
+        maze.furthest_distance().unwrap();
+        let components = maze.components();
+        assert_eq!(
+            vec![
+                (ComponentKind::Chain, 1, ((0, 0), (0, 0))),
+                (ComponentKind::Chain, 1, ((0, 1), (0, 1))),
+                (ComponentKind::Loop, 16, ((0, 0), (4, 4))),
+                (ComponentKind::Chain, 1, ((0, 4), (0, 4))),
+                (ComponentKind::Chain, 1, ((1, 4), (1, 4))),
+                (ComponentKind::Chain, 2, ((4, 3), (4, 4))),
+            ],
+            components
+                .iter()
+                .map(|c| (c.kind, c.length(), c.bounds))
+                .collect::<Vec<_>>()
+        );
+        let main = components[2].tiles.iter().copied().collect::<HashSet<_>>();
+        assert_eq!(maze.loop_hash, main);
+    }
+
+    #[test]
+    fn components_apart() {
+        // A loop apart from the main one, and one broken open
+        let maze = parse_str("F7.F7\nLJ.L-\n.....\n|F-7.").unwrap();
+        let components = maze.components();
+        assert_eq!(
+            vec![
+                (ComponentKind::Loop, 4, ((0, 0), (1, 1))),
+                (ComponentKind::Chain, 4, ((0, 3), (1, 4))),
+                (ComponentKind::Chain, 1, ((3, 0), (3, 0))),
+                (ComponentKind::Chain, 3, ((3, 1), (3, 3))),
+            ],
+            components
+                .iter()
+                .map(|c| (c.kind, c.length(), c.bounds))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec![(0, 3), (0, 4), (1, 3), (1, 4)], components[1].tiles);
+        assert!(parse_str("...").unwrap().components().is_empty());
+    }
+
+    #[test]
+    fn start_errors() {
+        let mut maze = parse_str("...\n...").unwrap();
+        assert_eq!(Err(MazeError::NoStart), maze.trace_loop());
+
+        let mut maze = parse_str(".S.\n...").unwrap();
+        assert_eq!(Err(MazeError::DeadEnd { start: (0, 1) }), maze.trace_loop());
+
+        // Both ways out of the start end on ground
+        let mut maze = parse_str("S-7\n|.|\nL-.").unwrap();
+        assert_eq!(Err(MazeError::DeadEnd { start: (0, 0) }), maze.trace_loop());
+        assert!(maze.furthest_distance().is_err());
+
+        // Two loops cross at the start
+        let mut maze = parse_str("F-7..\n|.|..\nL-S-7\n..|.|\n..L-J").unwrap();
+        assert_eq!(
+            Err(MazeError::Ambiguous {
+                start: (2, 2),
+                shapes: vec!['J', 'F']
+            }),
+            maze.trace_loop()
+        );
+    }
+
+    /// A loop snaking through every tile of a `width` by `height` grid, starting at the
+    /// top left. `height` has to be even.
+    fn snake(width: usize, height: usize) -> String {
+        let mut rows = Vec::new();
+        for row in 0..height {
+            let (first, last) = (row == 0, row == height - 1);
+            let line = (0..width)
+                .map(|column| match column {
+                    0 if first => 'S',
+                    0 if last => 'L',
+                    0 => '|',
+                    _ if column == width - 1 && first => '7',
+                    _ if column == width - 1 && last => 'J',
+                    _ if first || last => '-',
+                    1 if row % 2 == 1 => 'F',
+                    1 => 'L',
+                    _ if column == width - 1 && row % 2 == 1 => 'J',
+                    _ if column == width - 1 => '7',
+                    _ => '-',
+                })
+                .collect::<String>();
+            rows.push(line);
+        }
+        rows.join("\n")
+    }
+
+    #[test]
+    fn long_loop() {
+        // Far too long a loop to follow by recursion on the test thread's stack
+        let mut maze = parse_str(&snake(400, 400)).unwrap();
+        assert_eq!(80_000, maze.furthest_distance().unwrap());
+        assert_eq!('F', maze.start_is);
+        assert_eq!(0, maze.count_inside().unwrap());
+        assert_eq!(0, maze.count_inside_by_area().unwrap());
     }
 
-    /*
     #[test]
     fn result_inner_junk() {
-        let mut maze = parse(&example_p2_large_junk()).unwrap();
+        let mut maze = parse_str(example_p2_large_junk()).unwrap();
         assert!(maze.furthest_distance().is_ok());
-        assert_eq!(10, maze.count_inside(&example_p2_large_junk()).unwrap());
+        assert_eq!(10, maze.count_inside().unwrap());
     }
-    */
 }
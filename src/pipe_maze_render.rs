@@ -0,0 +1,220 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::{bail, Result};
+use std::io::Write;
+
+use crate::pipe_maze::Maze;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Style {
+    /// Coloured with escape codes, for a terminal
+    #[default]
+    Ansi,
+    /// A standalone page
+    Html,
+}
+
+impl Style {
+    pub fn from_arg(arg: &str) -> Result<Style> {
+        match arg {
+            "ansi" => Ok(Style::Ansi),
+            "html" => Ok(Style::Html),
+            _ => bail!("Invalid style {arg}, expected ansi or html"),
+        }
+    }
+}
+
+/// What a tile is highlighted as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Start,
+    Loop,
+    Inside,
+    Outside,
+}
+
+impl Role {
+    fn ansi(&self) -> &'static str {
+        match self {
+            Role::Start => "\x1b[1;31m",
+            Role::Loop => "\x1b[1;33m",
+            Role::Inside => "\x1b[30;42m",
+            Role::Outside => "\x1b[2m",
+        }
+    }
+
+    fn class(&self) -> &'static str {
+        match self {
+            Role::Start => "start",
+            Role::Loop => "loop",
+            Role::Inside => "inside",
+            Role::Outside => "outside",
+        }
+    }
+}
+
+/// Box-drawing character for a pipe, a dot for ground
+pub fn glyph(pipe: char) -> char {
+    match pipe {
+        '|' => '│',
+        '-' => '─',
+        'L' => '└',
+        'J' => '┘',
+        '7' => '┐',
+        'F' => '┌',
+        _ => '·',
+    }
+}
+
+/// Every row split into runs of tiles with the same role
+fn runs(maze: &Maze) -> Result<Vec<Vec<(Role, String)>>> {
+    let inside = maze.enclosed_tiles()?;
+    let start = maze.start.as_ref().map(|s| s.location);
+    let role = |pos| {
+        if Some(pos) == start {
+            Role::Start
+        } else if maze.loop_hash.contains(&pos) {
+            Role::Loop
+        } else if inside.contains(&pos) {
+            Role::Inside
+        } else {
+            Role::Outside
+        }
+    };
+
+    let mut result = Vec::new();
+    for row in 0..maze.maze.height() {
+        let mut runs: Vec<(Role, String)> = Vec::new();
+        for column in 0..maze.maze.width() {
+            let pos = (row, column);
+            let tile = glyph(maze.pipe_at(pos).unwrap_or('.'));
+            match runs.last_mut() {
+                Some((last, text)) if *last == role(pos) => text.push(tile),
+                _ => runs.push((role(pos), tile.to_string())),
+            }
+        }
+        result.push(runs);
+    }
+    Ok(result)
+}
+
+/// Draws the maze with box-drawing characters, the loop in yellow, its start in red and
+/// the tiles it encloses on green. The loop has to be traced, e.g. by
+/// `Maze::furthest_distance`.
+pub fn write_ansi<W: Write>(mut out: W, maze: &Maze) -> Result<()> {
+    for runs in runs(maze)? {
+        for (role, text) in runs {
+            write!(out, "{}{text}", role.ansi())?;
+        }
+        writeln!(out, "\x1b[0m")?;
+    }
+    Ok(())
+}
+
+/// Like `write_ansi`, as a standalone HTML page
+pub fn write_html<W: Write>(mut out: W, maze: &Maze) -> Result<()> {
+    let rows = runs(maze)?;
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(
+        out,
+        r#"<html><head><meta charset="utf-8"><title>Pipe maze</title>"#
+    )?;
+    writeln!(
+        out,
+        "<style>pre {{ line-height: 1; }} .start {{ color: #d01010; font-weight: bold; }} \
+         .loop {{ color: #c08000; font-weight: bold; }} .inside {{ background: #9be39b; }} \
+         .outside {{ color: #aaaaaa; }}</style>"
+    )?;
+    writeln!(out, "</head><body>")?;
+    let inside = rows
+        .iter()
+        .flatten()
+        .filter(|(role, _)| *role == Role::Inside)
+        .map(|(_, text)| text.chars().count())
+        .sum::<usize>();
+    writeln!(
+        out,
+        "<p>Loop of {} tiles enclosing {inside} tiles</p>",
+        maze.loop_hash.len()
+    )?;
+    writeln!(out, "<pre>")?;
+    for runs in rows {
+        for (role, text) in runs {
+            write!(out, r#"<span class="{}">{text}</span>"#, role.class())?;
+        }
+        writeln!(out)?;
+    }
+    writeln!(out, "</pre>")?;
+    writeln!(out, "</body></html>")?;
+    Ok(())
+}
+
+/// Draws the maze in `style`
+pub fn write<W: Write>(out: W, maze: &Maze, style: Style) -> Result<()> {
+    match style {
+        Style::Ansi => write_ansi(out, maze),
+        Style::Html => write_html(out, maze),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipe_maze::{self, example_squeeze};
+    use regex::Regex;
+
+    fn render(style: Style) -> String {
+        let mut maze = pipe_maze::parse_str(example_squeeze()).unwrap();
+        maze.furthest_distance().unwrap();
+        let mut text = Vec::new();
+        write(&mut text, &maze, style).unwrap();
+        String::from_utf8(text).unwrap()
+    }
+
+    #[test]
+    fn glyphs() {
+        assert_eq!(
+            "│─└┘┐┌··",
+            "|-LJ7F.S".chars().map(glyph).collect::<String>()
+        );
+    }
+
+    #[test]
+    fn render_ansi() {
+        let text = render(Style::Ansi);
+        let plain = Regex::new("\x1b\\[[0-9;]*m")
+            .unwrap()
+            .replace_all(&text, "");
+        assert_eq!(
+            "··········\n\
+             ·┌──────┐·\n\
+             ·│┌────┐│·\n\
+             ·││····││·\n\
+             ·││····││·\n\
+             ·│└─┐┌─┘│·\n\
+             ·│··││··│·\n\
+             ·└──┘└──┘·\n\
+             ··········\n",
+            plain
+        );
+        assert!(text.contains("\x1b[1;31m┌\x1b[1;33m──────┐"));
+        assert!(text.contains("\x1b[30;42m··\x1b[1;33m││\x1b[30;42m··"));
+        assert!(text.lines().all(|l| l.ends_with("\x1b[0m")));
+    }
+
+    #[test]
+    fn render_html() {
+        let text = render(Style::Html);
+        assert!(text.starts_with("<!DOCTYPE html>\n"));
+        assert!(text.contains("<p>Loop of 44 tiles enclosing 4 tiles</p>"));
+        assert!(text.contains(r#"<span class="start">┌</span><span class="loop">──────┐</span>"#));
+        assert_eq!(2, text.matches(r#"<span class="inside">··</span>"#).count());
+    }
+
+    #[test]
+    fn render_untraced() {
+        let maze = pipe_maze::parse_str(example_squeeze()).unwrap();
+        assert!(write_ansi(Vec::new(), &maze).is_err());
+    }
+}
@@ -0,0 +1,663 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::{bail, Context, Ok, Result};
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use crate::almanac;
+use crate::answers::{self, Answers, Status};
+use crate::bench;
+use crate::input::{HashingReader, InputSource};
+use crate::output::{Format, Output, Record};
+use crate::pipe_maze;
+use crate::pipe_maze_render::{self, Style};
+use crate::scaffold::Scaffold;
+use crate::solution::{self, Day, Part, Solution};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Print the answers
+    Run,
+    /// Compare the answers against the expected ones
+    Check,
+    /// Time parsing and solving over repeated runs
+    Bench,
+    /// Generate the files for a new day with the given module name
+    New { name: String },
+    /// Show how the almanac converts a seed to its location
+    Trace { seed: i64 },
+    /// Draw the pipe maze with its loop and the tiles it encloses
+    Render { style: Style },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    All,
+    Day(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Args {
+    pub command: Command,
+    pub selection: Selection,
+    pub parts: Vec<Part>,
+    pub input: InputSource,
+    /// Answers file used by `Command::Check`, `None` for the default one
+    pub answers: Option<PathBuf>,
+    /// Number of repetitions for `Command::Bench`
+    pub runs: usize,
+    /// CSV file the measurements of `Command::Bench` are written to
+    pub summary: Option<PathBuf>,
+    /// How `Command::Run` prints the answers
+    pub format: Format,
+}
+
+pub const DEFAULT_RUNS: usize = 10;
+
+impl Args {
+    pub fn day(day: u8) -> Args {
+        Args {
+            command: Command::Run,
+            selection: Selection::Day(day),
+            parts: Part::all(),
+            input: InputSource::Default,
+            answers: None,
+            runs: DEFAULT_RUNS,
+            summary: None,
+            format: Format::Plain,
+        }
+    }
+
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Args> {
+        let mut command = Command::Run;
+        let mut selection = None;
+        let mut parts = Part::all();
+        let mut input = None;
+        let mut answers = None;
+        let mut runs = DEFAULT_RUNS;
+        let mut summary = None;
+        let mut format = None;
+        let mut args = args.into_iter().peekable();
+        match args.peek().map(|a| a.as_str()) {
+            Some("check") => command = Command::Check,
+            Some("bench") => command = Command::Bench,
+            Some("new") => {
+                args.next();
+                return Args::parse_new(args);
+            }
+            Some("trace") => {
+                args.next();
+                return Args::parse_trace(args);
+            }
+            Some("render") => {
+                args.next();
+                return Args::parse_render(args);
+            }
+            _ => {}
+        }
+        if command != Command::Run {
+            args.next();
+        }
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--part" | "-p" => {
+                    let part = args.next().context("--part needs a value")?;
+                    parts = vec![parse_part(&part)?];
+                }
+                "--input" | "-i" => {
+                    let path = args.next().context("--input needs a value")?;
+                    input = Some(InputSource::from_arg(&path));
+                }
+                "--answers" => {
+                    let path = args.next().context("--answers needs a value")?;
+                    answers = Some(PathBuf::from(path));
+                }
+                "--runs" | "-n" => {
+                    let value = args.next().context("--runs needs a value")?;
+                    runs = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|r| *r > 0)
+                        .with_context(|| format!("Invalid number of runs {value}"))?;
+                }
+                "--format" | "-f" => {
+                    let value = args.next().context("--format needs a value")?;
+                    format = Some(Format::from_arg(&value)?);
+                }
+                "--summary" => {
+                    let path = args.next().context("--summary needs a value")?;
+                    summary = Some(PathBuf::from(path));
+                }
+                "all" => {
+                    selection = Some(Selection::All);
+                }
+                _ if selection.is_some() => {
+                    if input.is_some() {
+                        bail!("Unexpected argument {arg}");
+                    }
+                    input = Some(InputSource::from_arg(&arg));
+                }
+                _ => {
+                    let day = arg
+                        .parse::<u8>()
+                        .with_context(|| format!("Invalid day {arg}"))?;
+                    selection = Some(Selection::Day(day));
+                }
+            }
+        }
+        let selection = match selection {
+            Some(selection) => selection,
+            None if command != Command::Run => Selection::All,
+            None => bail!("No day given, expected a number or \"all\""),
+        };
+        if selection == Selection::All && input.is_some() {
+            bail!("An input can only be given for a single day");
+        }
+        if command != Command::Run && format.is_some() {
+            bail!("--format only applies to running days");
+        }
+        Ok(Args {
+            command,
+            selection,
+            parts,
+            input: input.unwrap_or(InputSource::Default),
+            answers,
+            runs,
+            summary,
+            format: format.unwrap_or_default(),
+        })
+    }
+}
+
+impl Args {
+    /// Arguments of a per-day binary, which are those of `aoc` without the day
+    pub fn parse_for_day<I: IntoIterator<Item = String>>(day: u8, args: I) -> Result<Args> {
+        let mut args = args.into_iter().peekable();
+        let first = args.peek().cloned();
+        match first.as_deref() {
+            Some("check" | "bench") => {
+                let command = args.next();
+                Args::parse(
+                    command
+                        .into_iter()
+                        .chain(iter::once(day.to_string()))
+                        .chain(args),
+                )
+            }
+            Some(command @ ("new" | "trace" | "render")) => {
+                bail!("{command} is only available as aoc {command}")
+            }
+            _ => Args::parse(iter::once(day.to_string()).chain(args)),
+        }
+    }
+
+    fn parse_new<I: Iterator<Item = String>>(mut args: I) -> Result<Args> {
+        let day = args.next().context("No day given for the new module")?;
+        let day = day
+            .parse::<u8>()
+            .with_context(|| format!("Invalid day {day}"))?;
+        let name = args.next().context("No name given for the new module")?;
+        if let Some(arg) = args.next() {
+            bail!("Unexpected argument {arg}");
+        }
+        Ok(Args {
+            command: Command::New { name },
+            ..Args::day(day)
+        })
+    }
+
+    fn parse_trace<I: Iterator<Item = String>>(mut args: I) -> Result<Args> {
+        let seed = args.next().context("No seed given to trace")?;
+        let seed = seed
+            .parse::<i64>()
+            .with_context(|| format!("Invalid seed {seed}"))?;
+        let mut input = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--input" | "-i" => {
+                    let path = args.next().context("--input needs a value")?;
+                    input = Some(InputSource::from_arg(&path));
+                }
+                _ if input.is_none() => input = Some(InputSource::from_arg(&arg)),
+                _ => bail!("Unexpected argument {arg}"),
+            }
+        }
+        Ok(Args {
+            command: Command::Trace { seed },
+            input: input.unwrap_or(InputSource::Default),
+            ..Args::day(almanac::Day05::DAY)
+        })
+    }
+
+    fn parse_render<I: Iterator<Item = String>>(mut args: I) -> Result<Args> {
+        let mut style = Style::default();
+        let mut input = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--style" | "-s" => {
+                    let value = args.next().context("--style needs a value")?;
+                    style = Style::from_arg(&value)?;
+                }
+                "--input" | "-i" => {
+                    let path = args.next().context("--input needs a value")?;
+                    input = Some(InputSource::from_arg(&path));
+                }
+                _ if input.is_none() => input = Some(InputSource::from_arg(&arg)),
+                _ => bail!("Unexpected argument {arg}"),
+            }
+        }
+        Ok(Args {
+            command: Command::Render { style },
+            input: input.unwrap_or(InputSource::Default),
+            ..Args::day(pipe_maze::Day10::DAY)
+        })
+    }
+}
+
+fn parse_part(part: &str) -> Result<Part> {
+    match part {
+        "1" => Ok(Part::One),
+        "2" => Ok(Part::Two),
+        _ => bail!("Invalid part {part}, expected 1 or 2"),
+    }
+}
+
+pub fn usage() -> &'static str {
+    "usage: aoc <day|all> [--part <1|2>] [[--input] <path|->] [--format <plain|json|csv>]
+       aoc check [day|all] [--part <1|2>] [[--input] <path|->] [--answers <path>]
+       aoc bench [day|all] [--part <1|2>] [[--input] <path|->] [--runs <n>] [--summary <path>]
+       aoc new <day> <module_name>
+       aoc trace <seed> [[--input] <path|->]
+       aoc render [--style <ansi|html>] [[--input] <path|->]"
+}
+
+fn run_day<R: BufRead, W: Write>(
+    day: &Day,
+    parts: &[Part],
+    input: R,
+    output: &mut Output<W>,
+) -> Result<()> {
+    let mut input = HashingReader::new(input);
+    let solved = (day.run)(&mut input, parts)?;
+    let input_hash = input.finish()?;
+    for answer in solved.answers {
+        output.write(&Record {
+            day: day.day,
+            name: day.name,
+            part: answer.part,
+            answer: answer.value,
+            duration: answer.time,
+            input_hash: input_hash.clone(),
+        })?;
+    }
+    Ok(())
+}
+
+fn check(days: &[Day], args: &Args) -> Result<ExitCode> {
+    let answers = match &args.answers {
+        Some(path) => Answers::load(path)?,
+        None => Answers::load(&Answers::default_path())?,
+    };
+    let mut results = Vec::new();
+    for day in days {
+        for result in answers::check_day(day, &args.parts, &args.input, &answers) {
+            println!(
+                "day {:02} part {}: {}",
+                result.day, result.part, result.status
+            );
+            results.push(result);
+        }
+    }
+
+    let passed = results.iter().filter(|r| r.status == Status::Pass).count();
+    let failed = results.iter().filter(|r| r.is_failure()).count();
+    let missing = results.len() - passed - failed;
+    println!("{passed} passed, {failed} failed, {missing} missing");
+    if failed > 0 {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+fn bench(days: &[Day], args: &Args) -> Result<ExitCode> {
+    let mut measurements = Vec::new();
+    for day in days {
+        let text = match args.input.read(day.day) {
+            Result::Ok(text) => text,
+            Err(e) if days.len() > 1 => {
+                println!("day {:02}: skipped ({e})", day.day);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        for m in bench::bench_day(day, &text, &args.parts, args.runs)? {
+            println!("day {:02} {}: {}", m.day, m.stage, m.stats);
+            measurements.push(m);
+        }
+    }
+    if let Some(path) = &args.summary {
+        let file =
+            File::create(path).with_context(|| format!("Could not create {}", path.display()))?;
+        bench::write_csv(BufWriter::new(file), &measurements)?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn trace(seed: i64, input: &InputSource) -> Result<ExitCode> {
+    let parsed = almanac::parse(input.open(almanac::Day05::DAY)?)?;
+    let steps = parsed.trace(almanac::SEED, almanac::LOCATION, seed)?;
+    almanac::write_trace(io::stdout().lock(), &steps)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn render(style: Style, input: &InputSource) -> Result<ExitCode> {
+    let mut maze = pipe_maze::parse(input.open(pipe_maze::Day10::DAY)?)?;
+    maze.furthest_distance()?;
+    pipe_maze_render::write(io::stdout().lock(), &maze, style)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn new_day(day: u8, name: &str) -> Result<ExitCode> {
+    let scaffold = Scaffold::new(day, name)?;
+    for path in scaffold.create(Path::new(env!("CARGO_MANIFEST_DIR")))? {
+        println!("wrote {}", path.display());
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+pub fn run(args: &Args) -> Result<ExitCode> {
+    if let (Command::New { name }, Selection::Day(day)) = (&args.command, &args.selection) {
+        return new_day(*day, name);
+    }
+    let days = match args.selection {
+        Selection::All => solution::days(),
+        Selection::Day(number) => {
+            vec![solution::day(number).with_context(|| format!("Day {number} not found"))?]
+        }
+    };
+    match args.command {
+        Command::Run => {
+            let mut output = Output::new(io::stdout().lock(), args.format, days.len() > 1);
+            output.begin()?;
+            for day in &days {
+                // Stdout may be JSON or CSV, so missing inputs are only reported on stderr
+                let input = match args.input.open(day.day) {
+                    Result::Ok(input) => input,
+                    Err(e) if days.len() > 1 => {
+                        eprintln!("day {:02}: skipped ({e})", day.day);
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+                run_day(day, &args.parts, input, &mut output)?;
+            }
+            output.finish()?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Check => check(&days, args),
+        Command::Bench => bench(&days, args),
+        Command::New { .. } => bail!("A new day needs a day number"),
+        Command::Trace { seed } => trace(seed, &args.input),
+        Command::Render { style } => render(style, &args.input),
+    }
+}
+
+/// Entry point shared by `aoc` and the per-day binaries
+pub fn main<I: IntoIterator<Item = String>>(args: I) -> Result<ExitCode> {
+    run_parsed(Args::parse(args))
+}
+
+fn run_parsed(args: Result<Args>) -> Result<ExitCode> {
+    let args = match args {
+        Result::Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{}", usage());
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    run(&args)
+}
+
+/// Entry point of the per-day binaries: like `main`, with the day already chosen
+pub fn main_for_day<I: IntoIterator<Item = String>>(day: u8, args: I) -> Result<ExitCode> {
+    run_parsed(Args::parse_for_day(day, args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Result<Args> {
+        Args::parse(s.split_whitespace().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn parse_args() {
+        assert_eq!(Args::day(5), args("5").unwrap());
+        assert_eq!(
+            Args {
+                command: Command::Run,
+                selection: Selection::Day(5),
+                parts: vec![Part::Two],
+                input: InputSource::Default,
+                answers: None,
+                runs: DEFAULT_RUNS,
+                summary: None,
+                format: Format::Plain,
+            },
+            args("5 --part 2").unwrap()
+        );
+        assert_eq!(
+            Args {
+                command: Command::Run,
+                selection: Selection::Day(5),
+                parts: Part::all(),
+                input: InputSource::Stdin,
+                answers: None,
+                runs: DEFAULT_RUNS,
+                summary: None,
+                format: Format::Plain,
+            },
+            args("5 -").unwrap()
+        );
+        assert_eq!(
+            Args {
+                command: Command::Run,
+                selection: Selection::Day(5),
+                parts: vec![Part::One],
+                input: InputSource::from_arg("other.txt"),
+                answers: None,
+                runs: DEFAULT_RUNS,
+                summary: None,
+                format: Format::Csv,
+            },
+            args("5 --input other.txt -p 1 --format csv").unwrap()
+        );
+        assert_eq!(
+            Args {
+                command: Command::Run,
+                selection: Selection::All,
+                parts: Part::all(),
+                input: InputSource::Default,
+                answers: None,
+                runs: DEFAULT_RUNS,
+                summary: None,
+                format: Format::Plain,
+            },
+            args("all").unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_check_args() {
+        assert_eq!(
+            Args {
+                command: Command::Check,
+                selection: Selection::All,
+                parts: Part::all(),
+                input: InputSource::Default,
+                answers: None,
+                runs: DEFAULT_RUNS,
+                summary: None,
+                format: Format::Plain,
+            },
+            args("check").unwrap()
+        );
+        assert_eq!(
+            Args {
+                command: Command::Check,
+                selection: Selection::Day(5),
+                parts: vec![Part::Two],
+                input: InputSource::Default,
+                answers: Some(PathBuf::from("mine.toml")),
+                runs: DEFAULT_RUNS,
+                summary: None,
+                format: Format::Plain,
+            },
+            args("check 5 -p 2 --answers mine.toml").unwrap()
+        );
+        assert!(args("check five").is_err());
+    }
+
+    #[test]
+    fn parse_bench_args() {
+        assert_eq!(
+            Args {
+                command: Command::Bench,
+                selection: Selection::All,
+                parts: Part::all(),
+                input: InputSource::Default,
+                answers: None,
+                runs: DEFAULT_RUNS,
+                summary: None,
+                format: Format::Plain,
+            },
+            args("bench").unwrap()
+        );
+        assert_eq!(
+            Args {
+                command: Command::Bench,
+                selection: Selection::Day(6),
+                parts: vec![Part::One],
+                input: InputSource::Default,
+                answers: None,
+                runs: 50,
+                summary: Some(PathBuf::from("times.csv")),
+                format: Format::Plain,
+            },
+            args("bench 6 -p 1 --runs 50 --summary times.csv").unwrap()
+        );
+        assert!(args("bench 6 --runs 0").is_err());
+        assert!(args("bench 6 -n many").is_err());
+    }
+
+    #[test]
+    fn parse_new_args() {
+        assert_eq!(
+            Args {
+                command: Command::New {
+                    name: "point_of_incidence".to_string()
+                },
+                ..Args::day(13)
+            },
+            args("new 13 point_of_incidence").unwrap()
+        );
+        assert!(args("new").is_err());
+        assert!(args("new 13").is_err());
+        assert!(args("new thirteen point_of_incidence").is_err());
+        assert!(args("new 13 point_of_incidence extra").is_err());
+    }
+
+    #[test]
+    fn parse_trace_args() {
+        assert_eq!(
+            Args {
+                command: Command::Trace { seed: 79 },
+                ..Args::day(5)
+            },
+            args("trace 79").unwrap()
+        );
+        assert_eq!(
+            Args {
+                command: Command::Trace { seed: 79 },
+                input: InputSource::Stdin,
+                ..Args::day(5)
+            },
+            args("trace 79 -").unwrap()
+        );
+        assert!(args("trace").is_err());
+        assert!(args("trace seventy").is_err());
+        assert!(args("trace 79 a.txt b.txt").is_err());
+    }
+
+    #[test]
+    fn parse_render_args() {
+        assert_eq!(
+            Args {
+                command: Command::Render { style: Style::Ansi },
+                ..Args::day(10)
+            },
+            args("render").unwrap()
+        );
+        assert_eq!(
+            Args {
+                command: Command::Render { style: Style::Html },
+                input: InputSource::Stdin,
+                ..Args::day(10)
+            },
+            args("render --style html -").unwrap()
+        );
+        assert!(args("render --style svg").is_err());
+        assert!(args("render a.txt b.txt").is_err());
+    }
+
+    #[test]
+    fn parse_day_args() {
+        let args = |day, s: &str| Args::parse_for_day(day, s.split_whitespace().map(String::from));
+        assert_eq!(Args::day(5), args(5, "").unwrap());
+        assert_eq!(
+            Args {
+                parts: vec![Part::One],
+                input: InputSource::Stdin,
+                ..Args::day(5)
+            },
+            args(5, "-p 1 -").unwrap()
+        );
+        assert_eq!(
+            Args {
+                command: Command::Check,
+                parts: vec![Part::Two],
+                ..Args::day(5)
+            },
+            args(5, "check -p 2").unwrap()
+        );
+        assert_eq!(
+            Args {
+                command: Command::Bench,
+                runs: 3,
+                ..Args::day(12)
+            },
+            args(12, "bench -n 3").unwrap()
+        );
+        assert!(args(5, "trace 79").is_err());
+        assert!(args(10, "render").is_err());
+        assert!(args(5, "new boat_race").is_err());
+    }
+
+    #[test]
+    fn parse_args_invalid() {
+        assert!(args("").is_err());
+        assert!(args("5 a.txt b.txt").is_err());
+        assert!(args("all a.txt").is_err());
+        assert!(args("5 --part 3").is_err());
+        assert!(args("five").is_err());
+        assert!(args("5 --format xml").is_err());
+        assert!(args("check --format json").is_err());
+    }
+}
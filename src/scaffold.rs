@@ -0,0 +1,430 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files for a new day: the solution module with a test skeleton, its binary, and the
+/// registration in `lib.rs` and `solution::days()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scaffold {
+    pub day: u8,
+    pub name: String,
+}
+
+const HEADER: &str =
+    "// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+";
+
+/// rustfmt's default maximum line width, used when rewrapping the imports
+const MAX_WIDTH: usize = 100;
+
+impl Scaffold {
+    pub fn new(day: u8, name: &str) -> Result<Scaffold> {
+        if !(1..=25).contains(&day) {
+            bail!("Invalid day {day}, expected 1 to 25");
+        }
+        let valid = name.starts_with(|c: char| c.is_ascii_lowercase())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid {
+            bail!("Invalid module name {name:?}, expected snake_case like \"hot_springs\"");
+        }
+        Ok(Scaffold {
+            day,
+            name: name.to_string(),
+        })
+    }
+
+    fn type_name(&self) -> String {
+        format!("Day{:02}", self.day)
+    }
+
+    pub fn module(&self) -> String {
+        format!(
+            r##"{HEADER}
+// 2023-{day:02}
+
+use anyhow::{{bail, Result}};
+use std::io::BufRead;
+
+use crate::error::ParseResult;
+use crate::input::LineReader;
+use crate::solution::Solution;
+
+pub fn parse<R: BufRead>(input: R) -> ParseResult<Vec<String>> {{
+    let mut lines = LineReader::new({ty}::DAY, input);
+    let mut result = Vec::new();
+    while let Some(line) = lines.next_line()? {{
+        result.push(line.text.to_string());
+    }}
+    Ok(result)
+}}
+
+pub fn parse_str(text: &str) -> ParseResult<Vec<String>> {{
+    parse(text.as_bytes())
+}}
+
+pub struct {ty};
+
+impl Solution for {ty} {{
+    const DAY: u8 = {day};
+    const NAME: &'static str = "{name}";
+
+    type Input = Vec<String>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {{
+        Ok(parse(input)?)
+    }}
+
+    fn part1(_input: &Self::Input) -> Result<i64> {{
+        bail!("Part 1 is not solved yet")
+    }}
+
+    fn part2(_input: &Self::Input) -> Result<i64> {{
+        bail!("Part 2 is not solved yet")
+    }}
+}}
+
+#[cfg(test)]
+mod tests {{
+    use super::*;
+
+    fn example() -> &'static str {{
+        r#""#
+    }}
+
+    #[test]
+    fn parse_example() {{
+        assert!(parse_str(example()).is_ok());
+    }}
+}}
+"##,
+            day = self.day,
+            ty = self.type_name(),
+            name = self.name,
+        )
+    }
+
+    pub fn binary(&self) -> String {
+        format!(
+            r#"{HEADER}
+use anyhow::Result;
+use std::env;
+use std::process::ExitCode;
+
+use advent_of_code_2023::runner;
+
+fn main() -> Result<ExitCode> {{
+    runner::main_for_day({day}, env::args().skip(1))
+}}
+"#,
+            day = self.day
+        )
+    }
+
+    /// Adds `pub mod` for the new module to `lib.rs`, keeping the modules sorted
+    pub fn register_module(&self, lib_rs: &str) -> Result<String> {
+        let declaration = format!("pub mod {};", self.name);
+        let mut lines = lib_rs.lines().collect::<Vec<_>>();
+        if lines.contains(&declaration.as_str()) {
+            bail!("Module {} already exists", self.name);
+        }
+        let modules = lines
+            .iter()
+            .position(|l| l.starts_with("pub mod "))
+            .context("No module declarations in lib.rs")?;
+        let position = lines[modules..]
+            .iter()
+            .position(|l| !l.starts_with("pub mod ") || **l > *declaration)
+            .map_or(lines.len(), |p| modules + p);
+        lines.insert(position, &declaration);
+        Ok(lines.join("\n") + "\n")
+    }
+
+    /// Adds the new day to the imports and to `days()` in `solution.rs`
+    pub fn register_day(&self, solution_rs: &str) -> Result<String> {
+        let entry = format!("Day::of::<{}::{}>(),", self.name, self.type_name());
+
+        let imports_start = solution_rs
+            .find("use crate::{")
+            .context("No day module imports in solution.rs")?;
+        let imports_end = imports_start
+            + solution_rs[imports_start..]
+                .find("};\n")
+                .context("Unterminated imports in solution.rs")?
+            + 3;
+        let mut modules = solution_rs[imports_start + "use crate::{".len()..imports_end - 3]
+            .split(',')
+            .map(|m| m.trim())
+            .filter(|m| !m.is_empty())
+            .collect::<Vec<_>>();
+        if modules.contains(&self.name.as_str()) {
+            bail!("Module {} is already registered", self.name);
+        }
+        modules.push(&self.name);
+        modules.sort();
+
+        let days_start = solution_rs
+            .find("pub fn days() -> Vec<Day> {\n    vec![\n")
+            .context("No days() in solution.rs")?
+            + "pub fn days() -> Vec<Day> {\n    vec![\n".len();
+        let days_end = days_start
+            + solution_rs[days_start..]
+                .find("    ]\n")
+                .context("Unterminated days() in solution.rs")?;
+        let mut days = solution_rs[days_start..days_end]
+            .lines()
+            .map(|l| l.trim())
+            .collect::<Vec<_>>();
+        let day_suffix = format!("::{}>(),", self.type_name());
+        if days.iter().any(|d| d.ends_with(&day_suffix)) {
+            bail!("Day {} is already registered", self.day);
+        }
+        days.push(&entry);
+        days.sort_by_key(|d| {
+            d.rsplit("::Day")
+                .next()
+                .and_then(|n| n.get(..2))
+                .and_then(|n| n.parse::<u8>().ok())
+        });
+
+        let mut result = String::new();
+        result.push_str(&solution_rs[..imports_start]);
+        result.push_str(&wrap_imports(&modules));
+        result.push_str(&solution_rs[imports_end..days_start]);
+        for day in days {
+            result.push_str(&format!("        {day}\n"));
+        }
+        result.push_str(&solution_rs[days_end..]);
+        Ok(result)
+    }
+
+    /// Writes all files below `root`, the directory containing `Cargo.toml`. Nothing is
+    /// written if any of the files for this day already exists.
+    pub fn create(&self, root: &Path) -> Result<Vec<PathBuf>> {
+        let src = root.join("src");
+        let module = src.join(format!("{}.rs", self.name));
+        let binary = src.join("bin").join(format!("{:02}.rs", self.day));
+        for path in [&module, &binary] {
+            if path.exists() {
+                bail!("{} already exists", path.display());
+            }
+        }
+        let lib_rs = src.join("lib.rs");
+        let solution_rs = src.join("solution.rs");
+        let lib = self.register_module(&read(&lib_rs)?)?;
+        let solution = self.register_day(&read(&solution_rs)?)?;
+
+        write(&module, &self.module())?;
+        write(&binary, &self.binary())?;
+        write(&lib_rs, &lib)?;
+        write(&solution_rs, &solution)?;
+        Ok(vec![module, binary, lib_rs, solution_rs])
+    }
+}
+
+/// `use crate::{...};` the way rustfmt lays it out
+fn wrap_imports(modules: &[&str]) -> String {
+    let single = format!("use crate::{{{}}};\n", modules.join(", "));
+    if single.len() - 1 <= MAX_WIDTH {
+        return single;
+    }
+    let mut result = "use crate::{\n".to_string();
+    let mut line = String::new();
+    for module in modules {
+        if !line.is_empty() && 4 + line.len() + 1 + module.len() + 1 > MAX_WIDTH {
+            result.push_str(&format!("    {line}\n"));
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(module);
+        line.push(',');
+    }
+    result.push_str(&format!("    {line}\n}};\n"));
+    result
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))
+}
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    fs::write(path, contents).with_context(|| format!("Could not write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution;
+
+    fn scaffold() -> Scaffold {
+        Scaffold::new(13, "point_of_incidence").unwrap()
+    }
+
+    /// A day which is not registered yet, for exercising the real `lib.rs` and
+    /// `solution.rs`
+    fn unregistered() -> Scaffold {
+        let registered = solution::days().iter().map(|d| d.day).collect::<Vec<_>>();
+        let day = (1..=25).find(|d| !registered.contains(d)).unwrap();
+        Scaffold::new(day, "zz_scaffold_test").unwrap()
+    }
+
+    fn solution_rs() -> &'static str {
+        r#"use anyhow::Result;
+
+use crate::{almanac, trebuchet};
+
+pub fn days() -> Vec<Day> {
+    vec![
+        Day::of::<trebuchet::Day01>(),
+        Day::of::<almanac::Day05>(),
+    ]
+}
+"#
+    }
+
+    #[test]
+    fn new() {
+        assert!(Scaffold::new(0, "foo").is_err());
+        assert!(Scaffold::new(26, "foo").is_err());
+        assert!(Scaffold::new(13, "Foo").is_err());
+        assert!(Scaffold::new(13, "foo-bar").is_err());
+        assert!(Scaffold::new(13, "1foo").is_err());
+        assert!(Scaffold::new(13, "").is_err());
+        assert_eq!(
+            Scaffold {
+                day: 13,
+                name: "foo_2".to_string()
+            },
+            Scaffold::new(13, "foo_2").unwrap()
+        );
+    }
+
+    #[test]
+    fn templates() {
+        let module = scaffold().module();
+        assert!(module.starts_with(HEADER));
+        assert!(module.contains("\n// 2023-13\n"));
+        assert!(module.contains("pub struct Day13;"));
+        assert!(module.contains("    const DAY: u8 = 13;\n"));
+        assert!(module.contains("const NAME: &'static str = \"point_of_incidence\";"));
+        assert!(module.contains("#[cfg(test)]\nmod tests {"));
+        assert!(module.contains("fn example() -> &'static str {"));
+
+        let binary = scaffold().binary();
+        assert!(binary.starts_with(HEADER));
+        assert!(binary.contains("runner::main_for_day(13, env::args().skip(1))"));
+        assert_eq!(
+            include_str!("bin/05.rs"),
+            Scaffold::new(5, "almanac").unwrap().binary()
+        );
+    }
+
+    #[test]
+    fn register_module() {
+        let lib = "// header\n\npub mod almanac;\npub mod trebuchet;\n";
+        assert_eq!(
+            "// header\n\npub mod almanac;\npub mod point_of_incidence;\npub mod trebuchet;\n",
+            scaffold().register_module(lib).unwrap()
+        );
+        assert_eq!(
+            "pub mod almanac;\npub mod zoo;\n",
+            Scaffold::new(13, "zoo")
+                .unwrap()
+                .register_module("pub mod almanac;\n")
+                .unwrap()
+        );
+        assert!(Scaffold::new(5, "almanac")
+            .unwrap()
+            .register_module(lib)
+            .is_err());
+
+        let lib = unregistered()
+            .register_module(include_str!("lib.rs"))
+            .unwrap();
+        assert!(lib.ends_with("\npub mod zz_scaffold_test;\n"));
+    }
+
+    #[test]
+    fn register_day() {
+        assert_eq!(
+            r#"use anyhow::Result;
+
+use crate::{almanac, point_of_incidence, trebuchet};
+
+pub fn days() -> Vec<Day> {
+    vec![
+        Day::of::<trebuchet::Day01>(),
+        Day::of::<almanac::Day05>(),
+        Day::of::<point_of_incidence::Day13>(),
+    ]
+}
+"#,
+            scaffold().register_day(solution_rs()).unwrap()
+        );
+        assert!(Scaffold::new(3, "gear_ratios")
+            .unwrap()
+            .register_day(solution_rs())
+            .unwrap()
+            .contains("Day01>(),\n        Day::of::<gear_ratios::Day03>(),\n        Day::"));
+        assert!(Scaffold::new(5, "other")
+            .unwrap()
+            .register_day(solution_rs())
+            .is_err());
+        assert!(Scaffold::new(13, "almanac")
+            .unwrap()
+            .register_day(solution_rs())
+            .is_err());
+
+        let scaffold = unregistered();
+        let solution = scaffold.register_day(include_str!("solution.rs")).unwrap();
+        assert!(solution.contains(&format!(
+            "Day::of::<zz_scaffold_test::Day{:02}>(),\n",
+            scaffold.day
+        )));
+        assert!(solution.lines().all(|l| l.len() <= MAX_WIDTH));
+    }
+
+    #[test]
+    fn imports() {
+        assert_eq!("use crate::{a, b};\n", wrap_imports(&["a", "b"]));
+        let long = ["abcdefghijklmnopqrstuvwxyz"; 5];
+        assert_eq!(
+            "use crate::{\n    abcdefghijklmnopqrstuvwxyz, abcdefghijklmnopqrstuvwxyz, \
+             abcdefghijklmnopqrstuvwxyz,\n    abcdefghijklmnopqrstuvwxyz, \
+             abcdefghijklmnopqrstuvwxyz,\n};\n",
+            wrap_imports(&long)
+        );
+    }
+
+    #[test]
+    fn create() {
+        let root = std::env::temp_dir().join(format!("aoc-scaffold-{}", std::process::id()));
+        fs::create_dir_all(root.join("src/bin")).unwrap();
+        fs::write(root.join("src/lib.rs"), include_str!("lib.rs")).unwrap();
+        fs::write(root.join("src/solution.rs"), include_str!("solution.rs")).unwrap();
+
+        let scaffold = unregistered();
+        let written = scaffold.create(&root).unwrap();
+        assert_eq!(4, written.len());
+        assert_eq!(
+            scaffold.module(),
+            fs::read_to_string(root.join("src/zz_scaffold_test.rs")).unwrap()
+        );
+        assert!(root
+            .join(format!("src/bin/{:02}.rs", scaffold.day))
+            .exists());
+        // A second run must not touch anything
+        assert!(scaffold.create(&root).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
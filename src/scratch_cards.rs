@@ -3,7 +3,12 @@
 
 // 2023-04
 
-use anyhow::{Context, Ok, Result};
+use anyhow::Result;
+use std::io::BufRead;
+
+use crate::error::ParseResult;
+use crate::input::LineReader;
+use crate::solution::Solution;
 
 #[derive(Debug)]
 pub struct Card {
@@ -34,7 +39,7 @@ impl Card {
     }
 }
 
-pub fn total(cards: &Vec<Card>) -> i32 {
+pub fn total(cards: &[Card]) -> i32 {
     let mut card_count = vec![1; cards.len()];
 
     for (card_idx, card) in cards.iter().enumerate() {
@@ -49,54 +54,73 @@ pub fn total(cards: &Vec<Card>) -> i32 {
     card_count.iter().sum()
 }
 
-pub fn parse(lines: &Vec<String>) -> Result<Vec<Card>> {
+pub fn parse<R: BufRead>(input: R) -> ParseResult<Vec<Card>> {
     let mut cards: Vec<Card> = vec![];
-    for line in lines {
-        let numbers = line
-            .split(':')
-            .last()
-            .context("Nothing behind colon")?
-            .split('|')
-            .collect::<Vec<_>>();
-        let goals: Vec<i32> = numbers
-            .first()
-            .context("No pipe found")?
+    let mut lines = LineReader::new(Day04::DAY, input);
+    while let Some(line) = lines.next_line()? {
+        let (_, numbers) = line.require(line.text.split_once(':'), "':'")?;
+        let (goals, actuals) = line.require(numbers.split_once('|'), "'|'")?;
+        let goals = goals
             .split(' ')
-            .filter_map(|g| g.parse::<i32>().ok())
-            .collect();
-        let actuals: Vec<i32> = numbers
-            .last()
-            .context("No pipe found")?
+            .filter(|g| !g.is_empty())
+            .map(|g| line.number::<i32>(g))
+            .collect::<ParseResult<Vec<_>>>()?;
+        let actuals = actuals
             .split(' ')
-            .filter_map(|a| a.parse::<i32>().ok())
-            .collect();
+            .filter(|a| !a.is_empty())
+            .map(|a| line.number::<i32>(a))
+            .collect::<ParseResult<Vec<_>>>()?;
         cards.push(Card::new(goals, actuals));
     }
 
     Ok(cards)
 }
 
+pub fn parse_str(text: &str) -> ParseResult<Vec<Card>> {
+    parse(text.as_bytes())
+}
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    const NAME: &'static str = "scratch_cards";
+
+    type Input = Vec<Card>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        Ok(input.iter().map(|c| c.worth()).sum())
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        Ok(total(input))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn example() -> Vec<String> {
+    fn example() -> &'static str {
         r#"Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
 Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
 Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
 Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
 Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
     #[test]
     fn sum_web() {
-        let cards = parse(&example()).unwrap();
+        let cards = parse_str(example()).unwrap();
         assert_eq!(6, cards.len());
-        assert_eq!(13, cards.iter().map(|c| c.worth()).sum());
+        assert_eq!(13, cards.iter().map(|c| c.worth()).sum::<i32>());
         assert_eq!(30, total(&cards));
     }
 }
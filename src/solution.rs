@@ -0,0 +1,161 @@
+// Copyright (C) 2023 Lennart Sauerbeck <devel at lennart dot sauerbeck dot org>
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::{Ok, Result};
+use std::fmt::{self, Display};
+use std::io::BufRead;
+use std::time::{Duration, Instant};
+
+use crate::{
+    almanac, boat_race, camel_cards, cosmic_expansion, cubebag, gear_ratios, haunted_wasteland,
+    hot_springs, oasis, pipe_maze, scratch_cards, trebuchet,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn all() -> Vec<Part> {
+        vec![Part::One, Part::Two]
+    }
+
+    pub fn number(&self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+/// One day of the calendar: parse the puzzle input once, then solve both parts on it.
+/// `parse_str` is a convenience for inputs which are already in memory.
+pub trait Solution {
+    const DAY: u8;
+    const NAME: &'static str;
+
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input>;
+    fn part1(input: &Self::Input) -> Result<Self::Answer1>;
+    fn part2(input: &Self::Input) -> Result<Self::Answer2>;
+
+    fn parse_str(text: &str) -> Result<Self::Input> {
+        Self::parse(text.as_bytes())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answer {
+    pub part: Part,
+    pub value: String,
+    /// Time spent solving this part, without parsing
+    pub time: Duration,
+}
+
+/// Result of one run of a day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solved {
+    pub parse_time: Duration,
+    pub answers: Vec<Answer>,
+}
+
+pub fn run<S: Solution>(input: &mut dyn BufRead, parts: &[Part]) -> Result<Solved> {
+    let start = Instant::now();
+    let input = S::parse(input)?;
+    let parse_time = start.elapsed();
+    let mut answers = Vec::new();
+    for part in parts {
+        let start = Instant::now();
+        let value = match part {
+            Part::One => S::part1(&input)?.to_string(),
+            Part::Two => S::part2(&input)?.to_string(),
+        };
+        answers.push(Answer {
+            part: *part,
+            value,
+            time: start.elapsed(),
+        });
+    }
+    Ok(Solved {
+        parse_time,
+        answers,
+    })
+}
+
+/// Type-erased handle on a `Solution` so that all days can be kept in one list.
+#[derive(Clone, Copy)]
+pub struct Day {
+    pub day: u8,
+    pub name: &'static str,
+    pub run: fn(&mut dyn BufRead, &[Part]) -> Result<Solved>,
+}
+
+impl Day {
+    pub fn of<S: Solution>() -> Day {
+        Day {
+            day: S::DAY,
+            name: S::NAME,
+            run: run::<S>,
+        }
+    }
+}
+
+impl fmt::Debug for Day {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Day({:02}, {})", self.day, self.name)
+    }
+}
+
+pub fn days() -> Vec<Day> {
+    vec![
+        Day::of::<trebuchet::Day01>(),
+        Day::of::<cubebag::Day02>(),
+        Day::of::<gear_ratios::Day03>(),
+        Day::of::<scratch_cards::Day04>(),
+        Day::of::<almanac::Day05>(),
+        Day::of::<boat_race::Day06>(),
+        Day::of::<camel_cards::Day07>(),
+        Day::of::<haunted_wasteland::Day08>(),
+        Day::of::<oasis::Day09>(),
+        Day::of::<pipe_maze::Day10>(),
+        Day::of::<cosmic_expansion::Day11>(),
+        Day::of::<hot_springs::Day12>(),
+    ]
+}
+
+pub fn day(day: u8) -> Option<Day> {
+    days().into_iter().find(|d| d.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_are_unique_and_sorted() {
+        let numbers = days().iter().map(|d| d.day).collect::<Vec<_>>();
+        assert_eq!((1..=12).collect::<Vec<u8>>(), numbers[..12]);
+        assert!(numbers.windows(2).all(|w| w[0] < w[1]));
+        assert!(numbers.iter().all(|d| (1..=25).contains(d)));
+    }
+
+    #[test]
+    fn run_single_part() {
+        let solved =
+            (day(1).unwrap().run)(&mut "1abc2\npqr3stu8vwx".as_bytes(), &[Part::One]).unwrap();
+        assert_eq!(1, solved.answers.len());
+        assert_eq!(Part::One, solved.answers[0].part);
+        assert_eq!("50", solved.answers[0].value);
+    }
+}
@@ -3,7 +3,12 @@
 
 // 2023-01
 
-use anyhow::{Ok, Result};
+use anyhow::{Context, Result};
+use std::io::BufRead;
+
+use crate::error::ParseResult;
+use crate::input::LineReader;
+use crate::solution::Solution;
 
 pub fn preprocess(lines: Vec<String>) -> Vec<String> {
     let mut result: Vec<String> = vec![];
@@ -101,30 +106,80 @@ pub fn preprocess(lines: Vec<String>) -> Vec<String> {
 
 pub fn count(lines: &[String]) -> Result<u32> {
     let mut numbers: Vec<u32> = vec![];
-    for mut line in lines.iter().cloned() {
+    for (i, mut line) in lines.iter().cloned().enumerate() {
         line.retain(|c| c.is_ascii_digit());
-        let first_nb = line.chars().next().unwrap().to_digit(10).unwrap();
+        let first_nb = line
+            .chars()
+            .next()
+            .with_context(|| format!("No digit in line {}", i + 1))?
+            .to_digit(10)
+            .unwrap();
         let second_nb = line.chars().last().unwrap().to_digit(10).unwrap();
         numbers.push(first_nb * 10 + second_nb);
     }
     Ok(numbers.iter().sum())
 }
 
+/// Digits as `preprocess` recognises them when spelled out
+const DIGIT_WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// Lines holding at least one digit, written as a number or spelled out
+pub fn parse<R: BufRead>(input: R) -> ParseResult<Vec<String>> {
+    let mut lines = LineReader::new(Day01::DAY, input);
+    let mut result = Vec::new();
+    while let Some(line) = lines.next_line()? {
+        if !line.text.chars().any(|c| c.is_ascii_digit())
+            && !DIGIT_WORDS.iter().any(|word| line.text.contains(word))
+        {
+            return Err(line.missing("digit"));
+        }
+        result.push(line.text.to_string());
+    }
+    Ok(result)
+}
+
+pub fn parse_str(text: &str) -> ParseResult<Vec<String>> {
+    parse(text.as_bytes())
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    const NAME: &'static str = "trebuchet";
+
+    type Input = Vec<String>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse<R: BufRead>(input: R) -> Result<Self::Input> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<u32> {
+        count(input)
+    }
+
+    fn part2(input: &Self::Input) -> Result<u32> {
+        count(&preprocess(input.clone()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ParseError;
 
-    fn example01() -> Vec<String> {
+    fn example01() -> &'static str {
         r#"1abc2
 pqr3stu8vwx
 a1b2c3d4e5f
 treb7uchet"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
-    fn example01_02() -> Vec<String> {
+    fn example01_02() -> &'static str {
         r#"two1nine
 eightwothree
 abcone2threexyz
@@ -132,14 +187,11 @@ xtwone3four
 4nineeightseven2
 zoneight234
 7pqrstsixteen"#
-            .split("\n")
-            .map(|s| s.to_string())
-            .collect()
     }
 
     #[test]
     fn count_web() {
-        let result = count(&example01());
+        let result = count(&parse_str(example01()).unwrap());
         assert_eq!(result.unwrap(), 142);
     }
 
@@ -176,7 +228,7 @@ zoneight234
 
     #[test]
     fn count_web2() {
-        let processed = &preprocess(example01_02());
+        let processed = &preprocess(parse_str(example01_02()).unwrap());
         assert_eq!(
             &vec![
                 "219",
@@ -195,4 +247,22 @@ zoneight234
         let result = count(processed);
         assert_eq!(result.unwrap(), 281);
     }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            ParseError::Unexpected {
+                day: 1,
+                line: 2,
+                column: 7,
+                expected: "digit",
+                found: String::new()
+            },
+            parse_str("1abc2\npqrstu\ntreb7uchet").unwrap_err()
+        );
+        // Spelled out digits only count for the second part
+        let lines = parse_str("eightwothree").unwrap();
+        assert!(count(&lines).is_err());
+        assert_eq!(83, count(&preprocess(lines)).unwrap());
+    }
 }